@@ -0,0 +1,92 @@
+use crate::error::{parse_lines, parse_num, ParseError};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// Confirmed answers for the real inputs, by day and part
+pub type Answers = HashMap<(u32, u32), String>;
+
+// The checked in answers, inputs/answers.txt
+pub fn default_path() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("inputs")
+        .join("answers.txt")
+}
+
+// Multiline answers (the day 10 CRT) are stored on one line with the
+// newlines escaped
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape(line: &str, s: &str) -> Result<String, ParseError> {
+    let mut res = String::new();
+    let mut chars = s.char_indices();
+    while let Some((i, c)) = chars.next() {
+        if c != '\\' {
+            res.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some((_, 'n')) => res.push('\n'),
+            Some((_, '\\')) => res.push('\\'),
+            _ => return Err(ParseError::new(line, &s[i..], "invalid escape")),
+        }
+    }
+
+    Ok(res)
+}
+
+// One "day part answer" line per answer
+pub fn format_answers(answers: &Answers) -> String {
+    let mut keys: Vec<&(u32, u32)> = answers.keys().collect();
+    keys.sort();
+
+    keys.iter()
+        .map(|key| format!("{:} {:} {:}\n", key.0, key.1, escape(&answers[key])))
+        .collect()
+}
+
+pub fn parse_answers(s: &str) -> Result<Answers, ParseError> {
+    parse_lines(s, |l| {
+        let parts: Vec<&str> = l.splitn(3, ' ').collect();
+        if parts.len() != 3 {
+            return Err(ParseError::new(l, l, "expected \"day part answer\", found"));
+        }
+
+        let key = (parse_num(l, parts[0])?, parse_num(l, parts[1])?);
+        Ok((key, unescape(l, parts[2])?))
+    })
+    .map(|entries| entries.into_iter().collect())
+}
+
+pub fn load(path: &Path) -> io::Result<Answers> {
+    let s = fs::read_to_string(path)?;
+    parse_answers(&s).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+pub fn save(path: &Path, answers: &Answers) -> io::Result<()> {
+    fs::write(path, format_answers(answers))
+}
+
+#[test]
+fn test_answers_roundtrip() {
+    let mut answers = Answers::new();
+    answers.insert((10, 2), String::from("#..#\n.\\.."));
+    answers.insert((2, 1), String::from("15"));
+    answers.insert((1, 2), String::from("45000"));
+
+    let s = format_answers(&answers);
+    assert_eq!(s, "1 2 45000\n2 1 15\n10 2 #..#\\n.\\\\..\n");
+    assert_eq!(parse_answers(&s), Ok(answers));
+
+    assert_eq!(
+        parse_answers("1 1 12\n3 x 7").map_err(|e| (e.line, e.column)),
+        Err((2, 3))
+    );
+    assert_eq!(
+        parse_answers("1 1 a\\tb").map_err(|e| (e.line, e.column)),
+        Err((1, 6))
+    );
+}
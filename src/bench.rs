@@ -0,0 +1,256 @@
+use crate::error::{parse_lines, parse_num, ParseError};
+use crate::solution::Solution;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::hint::black_box;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stage {
+    Parse,
+    Part1,
+    Part2,
+}
+
+// Timings of one stage in nanoseconds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub mean: f64,
+    pub variance: f64,
+    pub samples: usize,
+}
+
+// How many times to run each stage: until `max_samples` runs or `budget`
+// time has passed, but at least `min_samples` times.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Config {
+    pub min_samples: usize,
+    pub max_samples: usize,
+    pub budget: Duration,
+}
+
+// Stats for each stage of each day, e.g. from a previous run
+pub type Baseline = HashMap<(u32, Stage), Stats>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Change {
+    Faster(f64),
+    Slower(f64),
+    Regression(f64),
+}
+
+pub const STAGES: [Stage; 3] = [Stage::Parse, Stage::Part1, Stage::Part2];
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            min_samples: 2,
+            max_samples: 100,
+            budget: Duration::from_secs(1),
+        }
+    }
+}
+
+impl Stage {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stage::Parse => "parse",
+            Stage::Part1 => "part1",
+            Stage::Part2 => "part2",
+        }
+    }
+
+    pub fn from_name(s: &str) -> Option<Stage> {
+        STAGES.into_iter().find(|stage| stage.name() == s)
+    }
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:}", self.name())
+    }
+}
+
+impl Stats {
+    pub fn from_samples(samples: &[Duration]) -> Stats {
+        let ns: Vec<f64> = samples.iter().map(|d| d.as_nanos() as f64).collect();
+        let n = ns.len() as f64;
+        let mean = ns.iter().sum::<f64>() / n;
+        let variance = if ns.len() > 1 {
+            ns.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0)
+        } else {
+            0.0
+        };
+
+        Stats {
+            mean,
+            variance,
+            samples: ns.len(),
+        }
+    }
+
+    pub fn std_dev(&self) -> f64 {
+        self.variance.sqrt()
+    }
+
+    // Compare against a baseline. Slower by more than `threshold` (a
+    // fraction) and by more than twice the baseline's deviation counts as a
+    // regression.
+    pub fn compare(&self, baseline: &Stats, threshold: f64) -> Change {
+        let change = (self.mean - baseline.mean) / baseline.mean;
+        let noise = 2.0 * baseline.std_dev();
+
+        if change <= 0.0 {
+            Change::Faster(-change)
+        } else if change > threshold && self.mean - baseline.mean > noise {
+            Change::Regression(change)
+        } else {
+            Change::Slower(change)
+        }
+    }
+}
+
+pub fn measure<T, F: FnMut() -> T>(config: &Config, mut f: F) -> Stats {
+    let mut samples = vec![];
+    let start = Instant::now();
+
+    while samples.len() < config.min_samples
+        || (samples.len() < config.max_samples && start.elapsed() < config.budget)
+    {
+        let run_start = Instant::now();
+        black_box(f());
+        samples.push(run_start.elapsed());
+    }
+
+    Stats::from_samples(&samples)
+}
+
+// Time parsing and both parts of a day
+pub fn bench<S: Solution>(input: &str, config: &Config) -> Result<Vec<(Stage, Stats)>, ParseError> {
+    let parsed = S::parse(input)?;
+
+    Ok(vec![
+        (Stage::Parse, measure(config, || S::parse(black_box(input)))),
+        (
+            Stage::Part1,
+            measure(config, || S::part1(black_box(&parsed))),
+        ),
+        (
+            Stage::Part2,
+            measure(config, || S::part2(black_box(&parsed))),
+        ),
+    ])
+}
+
+// One "day stage mean variance samples" line per measurement
+pub fn format_baseline(baseline: &Baseline) -> String {
+    let mut keys: Vec<&(u32, Stage)> = baseline.keys().collect();
+    keys.sort_by_key(|(day, stage)| (*day, STAGES.iter().position(|s| s == stage)));
+
+    keys.iter()
+        .map(|key| {
+            let stats = baseline[key];
+            format!(
+                "{:} {:} {:.1} {:.1} {:}\n",
+                key.0, key.1, stats.mean, stats.variance, stats.samples
+            )
+        })
+        .collect()
+}
+
+pub fn parse_baseline(s: &str) -> Result<Baseline, ParseError> {
+    parse_lines(s, |l| {
+        let parts: Vec<&str> = l.split(' ').collect();
+        if parts.len() != 5 {
+            return Err(ParseError::new(l, l, "expected a baseline entry, found"));
+        }
+
+        let stage = Stage::from_name(parts[1])
+            .ok_or_else(|| ParseError::new(l, parts[1], "unknown stage"))?;
+        let stats = Stats {
+            mean: parse_num(l, parts[2])?,
+            variance: parse_num(l, parts[3])?,
+            samples: parse_num(l, parts[4])?,
+        };
+        Ok(((parse_num(l, parts[0])?, stage), stats))
+    })
+    .map(|entries| entries.into_iter().collect())
+}
+
+pub fn load_baseline(path: &Path) -> io::Result<Baseline> {
+    let s = fs::read_to_string(path)?;
+    parse_baseline(&s).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+pub fn save_baseline(path: &Path, baseline: &Baseline) -> io::Result<()> {
+    fs::write(path, format_baseline(baseline))
+}
+
+#[test]
+fn test_stats() {
+    let samples = [2, 4, 4, 4, 5, 5, 7, 9].map(Duration::from_nanos);
+    let stats = Stats::from_samples(&samples);
+
+    assert_eq!(stats.mean, 5.0);
+    assert_eq!(stats.variance, 32.0 / 7.0);
+    assert_eq!(stats.samples, 8);
+}
+
+#[test]
+fn test_compare() {
+    let baseline = Stats {
+        mean: 100.0,
+        variance: 4.0,
+        samples: 10,
+    };
+    let with_mean = |mean| Stats { mean, ..baseline };
+
+    assert_eq!(with_mean(90.0).compare(&baseline, 0.1), Change::Faster(0.1));
+    assert_eq!(
+        with_mean(105.0).compare(&baseline, 0.1),
+        Change::Slower(0.05)
+    );
+    assert_eq!(
+        with_mean(120.0).compare(&baseline, 0.1),
+        Change::Regression(0.2)
+    );
+
+    // within the noise of a jittery baseline
+    let noisy = Stats {
+        variance: 400.0,
+        ..baseline
+    };
+    assert_eq!(with_mean(120.0).compare(&noisy, 0.1), Change::Slower(0.2));
+}
+
+#[test]
+fn test_baseline_roundtrip() {
+    let mut baseline = Baseline::new();
+    baseline.insert(
+        (1, Stage::Part2),
+        Stats {
+            mean: 1500.5,
+            variance: 20.5,
+            samples: 100,
+        },
+    );
+    baseline.insert(
+        (1, Stage::Parse),
+        Stats {
+            mean: 300.0,
+            variance: 0.0,
+            samples: 3,
+        },
+    );
+
+    let s = format_baseline(&baseline);
+    assert_eq!(s, "1 parse 300.0 0.0 3\n1 part2 1500.5 20.5 100\n");
+    assert_eq!(parse_baseline(&s).map(|b| b.len()), Ok(2));
+    assert_eq!(
+        parse_baseline("1 part3 1.0 1.0 1").map_err(|e| e.column),
+        Err(3)
+    );
+}
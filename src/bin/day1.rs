@@ -0,0 +1,14 @@
+use aoc22::day1::{parse_input, solve, solve2};
+use aoc22::error::or_exit;
+
+fn main() {
+    let input = aoc22::input::from_args(1);
+    let input = or_exit(parse_input(&input));
+
+    let solution = solve(&input);
+
+    println!("The solution for problem 1 is {:?}", solution);
+
+    let solution2 = solve2(&input);
+    println!("The solution for problem 2 is {:?}", solution2);
+}
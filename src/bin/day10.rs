@@ -0,0 +1,58 @@
+use aoc22::day10::{
+    crt_pixels, draw_crt, parse, parse_cycles, query, read_crt, solve1, timeline, to_csv, Stop, Vm,
+    CRT_CYCLES,
+};
+use aoc22::error::or_exit;
+use aoc22::input::option;
+
+// day10 [input] [--trace] prints every cycle of the program to stderr
+// day10 [input] --cycles=20,60,100-140/20 prints x and the signal strength
+// during those cycles
+// day10 [input] --csv prints the registers during every cycle as CSV
+// All of them stop after --limit=N cycles, 240 by default.
+fn main() {
+    let input = aoc22::input::from_args(10);
+    let parsed = or_exit(parse(&input));
+    let limit = or_exit(option("limit").map_or(Ok(CRT_CYCLES), |l| {
+        l.parse::<u64>()
+            .map_err(|_| format!("Expected a number of cycles, found \"{:}\"", l))
+    }));
+
+    if option("trace").is_some() {
+        let mut vm = Vm::new(parsed.clone());
+        vm.trace = true;
+        match vm.run_until(limit + 1) {
+            Stop::Cycle => eprintln!("Stopped after {:} cycles", limit),
+            Stop::Overflow(r) => eprintln!("Register {:} overflowed", r.name()),
+            _ => (),
+        }
+    }
+
+    if let Some(cycles) = option("cycles") {
+        let cycles = or_exit(parse_cycles(&cycles));
+        let t = timeline(parsed, limit);
+        println!("{:>5}  {:>4}  Signal", "Cycle", "X");
+        for (cycle, sample) in cycles.iter().zip(query(&t, &cycles)) {
+            match sample {
+                Some(s) => println!("{:>5}  {:>4}  {:}", cycle, s.x(), s.signal_strength()),
+                None => println!("{:>5}  {:>4}  -", cycle, "-"),
+            }
+        }
+        return;
+    }
+
+    if option("csv").is_some() {
+        print!("{:}", to_csv(&timeline(parsed, limit)));
+        return;
+    }
+
+    let solution1 = solve1(parsed.clone());
+    let pixels = crt_pixels(parsed);
+
+    println!("Solution 1: {:}", solution1);
+    match read_crt(&pixels) {
+        Ok(letters) => println!("Solution 2: {:}", letters),
+        Err(e) => eprintln!("{:}", e),
+    }
+    draw_crt(pixels);
+}
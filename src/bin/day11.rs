@@ -0,0 +1,75 @@
+use aoc22::day11::{
+    checked_inspections, exact_inspections, get_mod, inspections_after, monkey_business, parse,
+    solve1, solve2, trace_item, Relief,
+};
+use aoc22::error::or_exit;
+use aoc22::input::option;
+
+// day11 [input] [--rounds=N] prints the monkey business after N rounds of
+// part 2 instead, which works for counts like 10^12 too.
+// day11 [input] --trace=M,I [--part=2] [--rounds=N] prints every throw of
+// the Ith item monkey M starts with, over the rounds of part 1 or 2.
+// day11 [input] --exact [--rounds=N] checks the part 2 inspections after N
+// rounds against exact arithmetic and reports where an i64 overflows.
+fn main() {
+    let input = aoc22::input::from_args(11);
+    let parsed = or_exit(parse(&input));
+    let mod_by = get_mod(&parsed);
+
+    if let Some(item) = option("trace") {
+        let (monkey, item) = or_exit(
+            item.split_once(',')
+                .and_then(|(m, i)| Some((m.parse().ok()?, i.parse().ok()?)))
+                .ok_or_else(|| format!("Expected --trace=MONKEY,ITEM, found \"{:}\"", item)),
+        );
+        let part2 = option("part").as_deref() == Some("2");
+        let rounds = option("rounds").map_or(Ok(if part2 { 10000 } else { 20 }), |r| number(&r));
+        let relief = if part2 {
+            Relief::Modulo(mod_by)
+        } else {
+            Relief::Divide(3)
+        };
+        match or_exit(trace_item(&parsed, monkey, item, or_exit(rounds), relief)) {
+            Some(trace) => trace.iter().for_each(|throw| println!("{:}", throw)),
+            None => or_exit(Err(format!("Monkey {:} has no item {:}", monkey, item))),
+        }
+        return;
+    }
+
+    if option("exact").is_some() {
+        let rounds = or_exit(option("rounds").map_or(Ok(20), |r| number(&r)));
+        let exact = or_exit(exact_inspections(&parsed, rounds, Relief::None));
+        let modular = or_exit(inspections_after(&parsed, mod_by, rounds));
+        println!("Exact inspections:   {:?}", exact);
+        println!("Modular inspections: {:?}", modular);
+        if exact != modular {
+            or_exit(Err::<(), _>("The modulus changes the inspections"));
+        }
+        if let Err(overflow) = checked_inspections(&parsed, rounds, Relief::None) {
+            println!("{:}", overflow);
+        }
+        return;
+    }
+
+    if let Some(rounds) = option("rounds") {
+        let rounds = or_exit(number(&rounds));
+        let inspects = or_exit(inspections_after(&parsed, mod_by, rounds));
+        println!(
+            "Monkey business after {:} rounds: {:}",
+            rounds,
+            monkey_business(&inspects)
+        );
+        return;
+    }
+
+    let solution1 = solve1(parsed.clone());
+    let solution2 = solve2(parsed, mod_by);
+
+    println!("Solution 1: {:}", solution1);
+    println!("Solution 2: {:}", solution2);
+}
+
+fn number(s: &str) -> Result<u64, String> {
+    s.parse()
+        .map_err(|_| format!("Expected a number of rounds, found \"{:}\"", s))
+}
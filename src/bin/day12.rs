@@ -0,0 +1,30 @@
+use aoc22::day12::{best_start, parse, render_path, search, solve1};
+use aoc22::error::or_exit;
+use aoc22::input::option;
+
+// day12 [input] [--path[=2]] draws the shortest route from the start, or
+// from the best low point, on the map instead.
+fn main() {
+    let input = aoc22::input::from_args(12);
+    let parsed = or_exit(parse(&input));
+
+    if let Some(part) = option("path") {
+        let start = match part.as_str() {
+            "2" => best_start(&parsed).map_or(parsed.start, |(start, _)| start),
+            _ => parsed.start,
+        };
+        match search(&parsed, [start]).path_to(parsed.goal) {
+            Some(path) => println!("{:}", render_path(&parsed, &path)),
+            None => or_exit(Err(format!("The goal can't be reached from {:}", start))),
+        }
+        return;
+    }
+
+    let solution1 = solve1(&parsed);
+
+    println!("Solution 1: {:}", solution1);
+    match best_start(&parsed) {
+        Some((start, dist)) => println!("Solution 2: {:} (starting at {:})", dist, start),
+        None => println!("Solution 2: the goal can't be reached from any low point"),
+    }
+}
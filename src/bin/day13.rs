@@ -0,0 +1,13 @@
+use aoc22::day13::{parse, parse2, solve1, solve2};
+use aoc22::error::or_exit;
+
+fn main() {
+    let input = aoc22::input::from_args(13);
+    let parsed = or_exit(parse(&input));
+    let solution1 = solve1(&parsed);
+    let mut parsed2 = or_exit(parse2(&input));
+    let solution2 = solve2(&mut parsed2);
+
+    println!("Solution 1: {:}", solution1);
+    println!("Solution 2: {:}", solution2);
+}
@@ -0,0 +1,14 @@
+use aoc22::day14::{build_map, parse, solve1, solve2};
+use aoc22::error::or_exit;
+
+fn main() {
+    let input = aoc22::input::from_args(14);
+    let parsed = or_exit(parse(&input));
+    let mut m = build_map(parsed);
+    let solution1 = solve1(&mut m.clone());
+
+    let solution2 = solve2(&mut m);
+
+    println!("Solution 1: {:}", solution1);
+    println!("Solution 2: {:}", solution2);
+}
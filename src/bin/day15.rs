@@ -0,0 +1,13 @@
+use aoc22::day15::{parse, solve1, solve2};
+use aoc22::error::or_exit;
+
+fn main() {
+    let input = aoc22::input::from_args(15);
+    let parsed = or_exit(parse(&input));
+
+    let solution1 = solve1(2000000, parsed.clone());
+    println!("Solution 1: {:}", solution1);
+
+    let solution2 = solve2(4000000, parsed);
+    println!("Solution 2: {:}", solution2);
+}
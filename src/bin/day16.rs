@@ -0,0 +1,51 @@
+use aoc22::day16::{pair_plan, plan, solo_plan, solve1, solve2, to_dot, Day16, START};
+use aoc22::error::or_exit;
+use aoc22::input::option;
+use aoc22::solution::Solution;
+use std::str::FromStr;
+
+// day16 [input] [--dot[=solo|pair]] prints the network as a DOT graph,
+// optionally with the route for part 1 or 2 highlighted.
+// day16 [input] [--agents=N] [--time=T] [--start=XX] prints the best plan
+// for any number of agents, time and start valve.
+fn main() {
+    let input = aoc22::input::from_args(16);
+    let parsed = or_exit(Day16::parse(&input));
+
+    if let Some(route) = option("dot") {
+        let plan = match route.as_str() {
+            "solo" => Some(solo_plan(&parsed, 30)),
+            "pair" => Some(pair_plan(&parsed, 26)),
+            _ => None,
+        };
+        print!("{:}", to_dot(&parsed, plan.as_ref()));
+        return;
+    }
+
+    let agents = option("agents");
+    let time = option("time");
+    let start = option("start");
+    if agents.is_some() || time.is_some() || start.is_some() {
+        let agents = agents.map_or(Ok(1), |a| number("agents", &a));
+        let time = time.map_or(Ok(30), |t| number("time", &t));
+        let start = start.unwrap_or_else(|| String::from(START));
+        match plan(&parsed, &start, or_exit(time), or_exit(agents)) {
+            Some(plan) => println!("{:}", plan),
+            None => or_exit(Err(format!("There's no valve called {:}", start))),
+        }
+        return;
+    }
+
+    let solution1 = solve1(&parsed);
+    let solution2 = solve2(&parsed);
+
+    println!("Solution 1: {:}", solution1);
+    println!("{:}\n", solo_plan(&parsed, 30));
+    println!("Solution 2: {:}", solution2);
+    println!("{:}", pair_plan(&parsed, 26));
+}
+
+fn number<T: FromStr>(name: &str, s: &str) -> Result<T, String> {
+    s.parse()
+        .map_err(|_| format!("Invalid --{:} \"{:}\", expected a number", name, s))
+}
@@ -0,0 +1,10 @@
+use aoc22::day2::{parse_input, solve1, solve2};
+use aoc22::error::or_exit;
+
+fn main() {
+    let input = aoc22::input::from_args(2);
+    let input = or_exit(parse_input(&input));
+
+    println!("Day 2 solution 1: {:?}", solve1(&input));
+    println!("Day 2 solution 2: {:?}", solve2(&input));
+}
@@ -0,0 +1,10 @@
+use aoc22::day3::{parse, solve1, solve2};
+use aoc22::error::or_exit;
+
+fn main() {
+    let input_str = aoc22::input::from_args(3);
+    let input = or_exit(parse(&input_str));
+
+    println!("Solution 1: {:?}", solve1(input.as_slice()));
+    println!("Solution 2: {:?}", solve2(input.as_slice()));
+}
@@ -0,0 +1,9 @@
+use aoc22::day4::{parse, solve1, solve2};
+use aoc22::error::or_exit;
+
+fn main() {
+    let input = aoc22::input::from_args(4);
+    let input = or_exit(parse(&input));
+    println!("Solution 1: {:?}", solve1(&input));
+    println!("Solution 2: {:?}", solve2(&input));
+}
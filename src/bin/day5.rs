@@ -0,0 +1,15 @@
+use aoc22::day5::{parse_input, solve1, solve2};
+use aoc22::error::or_exit;
+
+fn main() {
+    let input = aoc22::input::from_args(5);
+    let input = or_exit(parse_input(&input));
+    println!(
+        "Solution 1: {:}",
+        solve1(input.clone()).into_iter().collect::<String>()
+    );
+    println!(
+        "Solution 2: {:}",
+        solve2(input).into_iter().collect::<String>()
+    );
+}
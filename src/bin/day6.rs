@@ -0,0 +1,7 @@
+use aoc22::day6::{solve1, solve2};
+
+fn main() {
+    let input = aoc22::input::from_args(6);
+    println!("Solution 1: {:}", solve1(&input));
+    println!("Solution 2: {:}", solve2(&input));
+}
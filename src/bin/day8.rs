@@ -0,0 +1,12 @@
+use aoc22::day8::{parse, solve1, solve2};
+use aoc22::error::or_exit;
+
+fn main() {
+    let input = aoc22::input::from_args(8);
+    let parsed = or_exit(parse(&input));
+    let solution1 = solve1(&parsed);
+    let solution2 = solve2(&parsed);
+
+    println!("Solution 1: {:}", solution1);
+    println!("Solution 2: {:}", solution2);
+}
@@ -1,25 +1,36 @@
-type Input = Vec<Vec<i64>>;
+use crate::error::{parse_num, ParseError};
+use crate::solution::Solution;
 
-fn main() {
-    const INPUT: &str = include_str!("../inputs/day1.txt");
-    let input = parse_input(INPUT).unwrap();
+pub type Input = Vec<Vec<i64>>;
 
-    let solution = solve(&input);
+pub struct Day1;
 
-    println!("The solution for problem 1 is {:?}", solution);
+impl Solution for Day1 {
+    type Input = Input;
+    type Answer1 = i64;
+    type Answer2 = i64;
 
-    let solution2 = solve2(&input);
-    println!("The solution for problem 2 is {:?}", solution2);
+    fn parse(input: &str) -> Result<Input, ParseError> {
+        parse_input(input)
+    }
+
+    fn part1(input: &Input) -> i64 {
+        solve(input)
+    }
+
+    fn part2(input: &Input) -> i64 {
+        solve2(input)
+    }
 }
 
-fn solve(calorie_lists: &Input) -> i64 {
+pub fn solve(calorie_lists: &Input) -> i64 {
     let elf_calories: Vec<i64> = calorie_lists.iter().map(|list| list.iter().sum()).collect();
     let maxval = elf_calories.iter().max().unwrap_or(&0);
 
     *maxval
 }
 
-fn solve2(calorie_lists: &Input) -> i64 {
+pub fn solve2(calorie_lists: &Input) -> i64 {
     let mut elf_calories: Vec<i64> = calorie_lists.iter().map(|list| list.iter().sum()).collect();
     elf_calories.sort();
 
@@ -28,16 +39,16 @@ fn solve2(calorie_lists: &Input) -> i64 {
         .sum()
 }
 
-fn parse_input(input_str: &str) -> Result<Input, std::io::Error> {
+pub fn parse_input(input_str: &str) -> Result<Input, ParseError> {
     let mut output: Input = vec![];
     let mut cur_group = vec![];
 
-    for line in input_str.lines() {
+    for (i, line) in input_str.lines().enumerate() {
         if line.is_empty() {
             output.push(cur_group);
             cur_group = vec![];
         } else {
-            let num = line.parse().unwrap();
+            let num = parse_num(line, line).map_err(|e| e.offset(i))?;
             cur_group.push(num);
         }
     }
@@ -51,6 +62,9 @@ fn test_parser() {
     assert_eq!(parse_input("").unwrap(), vec![vec![]]);
     assert_eq!(parse_input("1\n2").unwrap(), vec![vec![1, 2]]);
     assert_eq!(parse_input("1\n2\n\n3").unwrap(), vec![vec![1, 2], vec![3]]);
+
+    let err = parse_input("1\n2\n\nx3").unwrap_err();
+    assert_eq!((err.line, err.column, err.text.as_str()), (4, 1, "x3"));
 }
 
 #[test]
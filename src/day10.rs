@@ -1,39 +1,162 @@
+use crate::error::{parse_lines, parse_num, ParseError};
+use crate::ocr::{self, OcrError};
+use crate::solution::Solution;
 use num::abs;
+use std::fmt;
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reg {
+    X,
+    Y,
+}
+
+pub const REGISTERS: [Reg; 2] = [Reg::X, Reg::Y];
+
+// What an instruction does once it has taken all its cycles
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Effect {
+    None,
+    Add(Reg),
+    Mul(Reg),
+    // move by the operand relative to the instruction
+    Jump,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Opcode {
+    pub mnemonic: &'static str,
+    pub cycles: u64,
+    pub effect: Effect,
+}
+
+// Every instruction takes one number as its operand, except the ones with no
+// effect
+pub const INSTRUCTIONS: [Opcode; 6] = [
+    Opcode {
+        mnemonic: "noop",
+        cycles: 1,
+        effect: Effect::None,
+    },
+    Opcode {
+        mnemonic: "addx",
+        cycles: 2,
+        effect: Effect::Add(Reg::X),
+    },
+    Opcode {
+        mnemonic: "addy",
+        cycles: 2,
+        effect: Effect::Add(Reg::Y),
+    },
+    Opcode {
+        mnemonic: "mulx",
+        cycles: 3,
+        effect: Effect::Mul(Reg::X),
+    },
+    Opcode {
+        mnemonic: "muly",
+        cycles: 3,
+        effect: Effect::Mul(Reg::Y),
+    },
+    Opcode {
+        mnemonic: "jmp",
+        cycles: 1,
+        effect: Effect::Jump,
+    },
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct I {
+    pub op: &'static Opcode,
+    pub arg: i64,
+}
+pub type State = i64;
+
+// The screen is drawn during the first 240 cycles, so nothing after them is
+// needed for either part. Programs can loop forever with jmp.
+pub const CRT_CYCLES: u64 = 240;
+
+// The handheld's CPU. The registers hold their values during `cycle`.
 #[derive(Clone, Debug)]
-enum I {
-    Addx(i64),
-    Noop,
+pub struct Vm {
+    pub program: Vec<I>,
+    pub regs: [i64; REGISTERS.len()],
+    pub pc: usize,
+    pub cycle: u64,
+    // cycles spent on the current instruction so far
+    busy: u64,
+    pub breakpoints: Vec<Breakpoint>,
+    // print each cycle to stderr
+    pub trace: bool,
+    // the register an instruction overflowed, which ends the program
+    pub overflow: Option<Reg>,
 }
-type State = i64;
 
-fn main() {
-    const INPUT: &str = include_str!("../inputs/day10.txt");
-    let parsed = parse(INPUT);
-    let solution1 = solve1(parsed.clone());
-    let pixels = crt_pixels(parsed);
+// Stop before a cycle, or once a register changes to a value
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Breakpoint {
+    Cycle(u64),
+    Register(Reg, i64),
+}
 
-    println!("Solution 1: {:}", solution1);
-    draw_crt(pixels);
+// The registers during a cycle
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sample {
+    pub cycle: u64,
+    pub regs: [i64; REGISTERS.len()],
 }
 
-fn solve1(is: Vec<I>) -> i64 {
-    let initial = 1;
-    let binding = run(initial, is);
-    let states: Vec<&State> = binding.iter().collect();
+// Why the VM stopped running
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Stop {
+    Cycle,
+    Breakpoint(Breakpoint),
+    Halted,
+    Overflow(Reg),
+}
 
-    let strengths = states
-        .iter()
-        .enumerate()
-        .map(|(i, s)| (i + 1) as i64 * (*s));
+pub struct Day10;
+
+impl Solution for Day10 {
+    type Input = Vec<I>;
+    type Answer1 = i64;
+    type Answer2 = String;
 
-    strengths.skip(19).step_by(40).sum()
+    fn parse(input: &str) -> Result<Vec<I>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Vec<I>) -> i64 {
+        solve1(input.clone())
+    }
+
+    // The letters on the screen, or the screen itself if they can't be read
+    fn part2(input: &Vec<I>) -> String {
+        let pixels = crt_pixels(input.clone());
+        match read_crt(&pixels) {
+            Ok(letters) => letters,
+            Err(e) => format!("{:}\n{:}", e, render_crt(pixels)),
+        }
+    }
+}
+
+// The signal strength during cycles 20, 60, 100 and so on
+pub fn solve1(is: Vec<I>) -> i64 {
+    let t = timeline(is, CRT_CYCLES);
+    let cycles: Vec<u64> = (20..=t.len() as u64).step_by(40).collect();
+
+    query(&t, &cycles)
+        .iter()
+        .flatten()
+        .map(|s| s.signal_strength())
+        .sum()
 }
 
-fn crt_pixels(is: Vec<I>) -> Vec<char> {
+pub fn crt_pixels(is: Vec<I>) -> Vec<char> {
     let initial = 1;
-    let binding = run(initial, is);
-    let states: Vec<&State> = binding.iter().collect();
+    let binding = run(initial, is, CRT_CYCLES);
+    // one pixel is drawn during each cycle, so the value after the last
+    // one isn't needed
+    let states: Vec<&State> = binding.iter().take(binding.len() - 1).collect();
 
     states
         .iter()
@@ -48,53 +171,417 @@ fn crt_pixels(is: Vec<I>) -> Vec<char> {
         .collect()
 }
 
-fn draw_crt(pixels: Vec<char>) {
-    for row in pixels.as_slice().chunks(40) {
-        println!("{:}", row.iter().cloned().collect::<String>());
+pub fn read_crt(pixels: &[char]) -> Result<String, OcrError> {
+    ocr::recognize(pixels, 40)
+}
+
+pub fn draw_crt(pixels: Vec<char>) {
+    println!("{:}", render_crt(pixels));
+}
+
+pub fn render_crt(pixels: Vec<char>) -> String {
+    pixels
+        .as_slice()
+        .chunks(40)
+        .map(|row| row.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn parse(s: &str) -> Result<Vec<I>, ParseError> {
+    parse_lines(s, parse_line)
+}
+
+fn parse_line(s: &str) -> Result<I, ParseError> {
+    let (mnemonic, arg) = match s.split_once(' ') {
+        Some((mnemonic, arg)) => (mnemonic, Some(arg)),
+        None => (s, None),
+    };
+    let op = INSTRUCTIONS
+        .iter()
+        .find(|op| op.mnemonic == mnemonic)
+        .ok_or_else(|| ParseError::new(s, mnemonic, "unknown instruction"))?;
+
+    let arg = match (op.effect, arg) {
+        (Effect::None, None) => 0,
+        (Effect::None, Some(arg)) => return Err(ParseError::new(s, arg, "unexpected operand")),
+        (_, Some(arg)) => parse_num(s, arg)?,
+        (_, None) => return Err(ParseError::new(s, s, "expected an operand, found")),
+    };
+
+    Ok(I { op, arg })
+}
+
+impl I {
+    // How many cycles the instruction takes to complete
+    pub fn cycles(&self) -> u64 {
+        self.op.cycles
     }
 }
 
-fn parse(s: &str) -> Vec<I> {
-    s.lines().map(parse_line).collect()
+impl fmt::Display for I {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.op.effect {
+            Effect::None => write!(f, "{:}", self.op.mnemonic),
+            _ => write!(f, "{:} {:}", self.op.mnemonic, self.arg),
+        }
+    }
 }
 
-fn parse_line(s: &str) -> I {
-    if s == "noop" {
-        I::Noop
-    } else {
-        I::Addx(s.split(' ').nth(1).unwrap().parse().unwrap())
+impl Reg {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Reg::X => "x",
+            Reg::Y => "y",
+        }
     }
 }
 
-fn do_step(s: State, i: I) -> Vec<State> {
-    println!("state: {:?}, i: {:?}", s, i);
-    match i {
-        I::Noop => vec![s],
-        I::Addx(x) => vec![s, s + x],
+impl Vm {
+    pub fn new(program: Vec<I>) -> Vm {
+        let mut regs = [0; REGISTERS.len()];
+        regs[Reg::X as usize] = 1;
+
+        Vm {
+            program,
+            regs,
+            pc: 0,
+            cycle: 1,
+            busy: 0,
+            breakpoints: vec![],
+            trace: false,
+            overflow: None,
+        }
+    }
+
+    pub fn reg(&self, r: Reg) -> i64 {
+        self.regs[r as usize]
+    }
+
+    pub fn x(&self) -> i64 {
+        self.reg(Reg::X)
+    }
+
+    pub fn halted(&self) -> bool {
+        self.pc >= self.program.len()
+    }
+
+    // Run the current cycle. Returns the value of x during it, or None if
+    // the program has ended.
+    pub fn step(&mut self) -> Option<i64> {
+        let i = self.program.get(self.pc)?.clone();
+        let during = self.x();
+        if self.trace {
+            let regs: Vec<String> = REGISTERS
+                .iter()
+                .map(|r| format!("{:} {:>4}", r.name(), self.reg(*r)))
+                .collect();
+            eprintln!("cycle {:>4}  {:}  {:}", self.cycle, regs.join("  "), i);
+        }
+
+        self.busy += 1;
+        if self.busy == i.cycles() {
+            self.pc += 1;
+            match i.op.effect {
+                Effect::None => (),
+                Effect::Add(r) => self.set(r, self.reg(r).checked_add(i.arg)),
+                Effect::Mul(r) => self.set(r, self.reg(r).checked_mul(i.arg)),
+                // jumping before the start or out of range ends the program
+                Effect::Jump => {
+                    self.pc = (self.pc as i64 - 1)
+                        .checked_add(i.arg)
+                        .and_then(|pc| pc.try_into().ok())
+                        .unwrap_or(self.program.len())
+                }
+            }
+            self.busy = 0;
+        }
+        self.cycle += 1;
+
+        Some(during)
+    }
+
+    // Store the result of an instruction, or end the program if it doesn't
+    // fit in the register
+    fn set(&mut self, r: Reg, value: Option<i64>) {
+        match value {
+            Some(v) => self.regs[r as usize] = v,
+            None => {
+                self.overflow = Some(r);
+                self.pc = self.program.len();
+            }
+        }
+    }
+
+    fn cycle_breakpoint(&self) -> Option<Breakpoint> {
+        self.breakpoints
+            .iter()
+            .copied()
+            .find(|b| *b == Breakpoint::Cycle(self.cycle))
+    }
+
+    // A breakpoint on a register that has changed from `before`
+    fn register_breakpoint(&self, before: &[i64]) -> Option<Breakpoint> {
+        self.breakpoints.iter().copied().find(|b| match b {
+            Breakpoint::Register(r, v) => self.reg(*r) == *v && before[*r as usize] != *v,
+            Breakpoint::Cycle(_) => false,
+        })
+    }
+
+    // Run until a breakpoint is hit or the program ends. Programs that loop
+    // forever need `run_until` instead.
+    pub fn run(&mut self) -> Stop {
+        self.run_until(u64::MAX)
     }
+
+    // Run until the given cycle is about to start, so that x is the value
+    // during it. A breakpoint on the cycle the VM is stopped at is skipped
+    // so that it can be resumed.
+    pub fn run_until(&mut self, cycle: u64) -> Stop {
+        let mut first = true;
+        while self.cycle < cycle {
+            if let Some(b) = self.cycle_breakpoint().filter(|_| !first) {
+                return Stop::Breakpoint(b);
+            }
+            first = false;
+
+            let before = self.regs;
+            if self.step().is_none() || self.overflow.is_some() {
+                return match self.overflow {
+                    Some(r) => Stop::Overflow(r),
+                    None => Stop::Halted,
+                };
+            }
+            if let Some(b) = self.register_breakpoint(&before) {
+                return Stop::Breakpoint(b);
+            }
+        }
+        Stop::Cycle
+    }
+}
+
+// The value of x during each cycle, at most `limit` of them, and the value
+// after the last one
+pub fn run(initial_s: State, is: Vec<I>, limit: u64) -> Vec<State> {
+    let mut vm = Vm::new(is);
+    vm.regs[Reg::X as usize] = initial_s;
+
+    let mut states: Vec<State> = std::iter::from_fn(|| vm.step())
+        .take(limit as usize)
+        .collect();
+    states.push(vm.x());
+    states
+}
+
+impl Sample {
+    pub fn x(&self) -> i64 {
+        self.regs[Reg::X as usize]
+    }
+
+    pub fn signal_strength(&self) -> i64 {
+        self.cycle as i64 * self.x()
+    }
+}
+
+// The registers during each cycle of the program, up to `limit` cycles
+pub fn timeline(is: Vec<I>, limit: u64) -> Vec<Sample> {
+    let mut vm = Vm::new(is);
+    let mut samples = vec![];
+    while !vm.halted() && vm.cycle <= limit {
+        samples.push(Sample {
+            cycle: vm.cycle,
+            regs: vm.regs,
+        });
+        vm.step();
+    }
+    samples
 }
 
-fn run(initial_s: State, is: Vec<I>) -> Vec<State> {
-    let mut ret = vec![initial_s];
-    let mut run_states = is
+// The samples for some cycles, None for the ones outside the program
+pub fn query(timeline: &[Sample], cycles: &[u64]) -> Vec<Option<Sample>> {
+    cycles
         .iter()
-        .scan(initial_s, |s, i| {
-            let next_states = do_step(*s, i.clone());
-            *s = *next_states.last().unwrap();
-            Some(next_states)
+        .map(|c| {
+            let i = c.checked_sub(1)?;
+            timeline.get(i as usize).copied()
         })
-        .flatten()
-        .collect();
+        .collect()
+}
+
+// A list of cycles like "20,60,100-140" or a range with a step like
+// "20-220/40"
+pub fn parse_cycles(s: &str) -> Result<Vec<u64>, ParseError> {
+    let mut cycles = vec![];
+    for part in s.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, parse_num(s, step)?),
+            None => (part, 1),
+        };
+        let (start, end): (u64, u64) = match range.split_once('-') {
+            Some((start, end)) => (parse_num(s, start)?, parse_num(s, end)?),
+            None => {
+                let cycle = parse_num(s, range)?;
+                (cycle, cycle)
+            }
+        };
+        if step == 0 {
+            return Err(ParseError::new(
+                s,
+                part,
+                "expected a step of at least 1, found",
+            ));
+        }
+        if start > end {
+            return Err(ParseError::new(
+                s,
+                range,
+                "expected a range from low to high, found",
+            ));
+        }
+        cycles.extend((start..=end).step_by(step));
+    }
 
-    ret.append(&mut run_states);
-    ret
+    Ok(cycles)
+}
+
+pub fn to_csv(timeline: &[Sample]) -> String {
+    let names: Vec<&str> = REGISTERS.iter().map(|r| r.name()).collect();
+    let mut csv = format!("cycle,{:},signal_strength\n", names.join(","));
+    for sample in timeline {
+        let regs: Vec<String> = sample.regs.iter().map(|r| r.to_string()).collect();
+        csv += &format!(
+            "{:},{:},{:}\n",
+            sample.cycle,
+            regs.join(","),
+            sample.signal_strength()
+        );
+    }
+    csv
+}
+
+#[test]
+fn test_parse() {
+    let err = parse("noop\naddx 3\naddx\nnoop").unwrap_err();
+    assert_eq!((err.line, err.text.as_str()), (3, "addx"));
+    assert_eq!(parse("addx x").map_err(|e| e.column), Err(6));
 }
 
 #[test]
 fn test_solve1() {
     const INPUT: &str = include_str!("../inputs/day10_test.txt");
-    let parsed = parse(INPUT);
+    let parsed = parse(INPUT).unwrap();
     let solution1 = solve1(parsed);
 
     assert_eq!(solution1, 13140);
 }
+
+#[test]
+fn test_vm() {
+    let mut vm = Vm::new(parse("noop\naddx 3\naddx -5").unwrap());
+
+    assert_eq!(vm.step(), Some(1));
+    assert_eq!((vm.cycle, vm.x()), (2, 1));
+    assert_eq!(vm.step(), Some(1));
+    assert_eq!(vm.step(), Some(1));
+    assert_eq!((vm.cycle, vm.x()), (4, 4));
+
+    assert_eq!(vm.run_until(5), Stop::Cycle);
+    assert_eq!(vm.x(), 4);
+    assert_eq!(vm.run(), Stop::Halted);
+    assert_eq!((vm.cycle, vm.x()), (6, -1));
+    assert_eq!(vm.step(), None);
+}
+
+#[test]
+fn test_breakpoints() {
+    const INPUT: &str = include_str!("../inputs/day10_test.txt");
+    let mut vm = Vm::new(parse(INPUT).unwrap());
+    vm.breakpoints = vec![Breakpoint::Cycle(20), Breakpoint::Register(Reg::X, 4)];
+
+    assert_eq!(vm.run(), Stop::Breakpoint(Breakpoint::Register(Reg::X, 4)));
+    assert_eq!(vm.cycle, 15);
+    assert_eq!(vm.run(), Stop::Breakpoint(Breakpoint::Cycle(20)));
+    assert_eq!(vm.x() * 20, 420);
+    assert_eq!(vm.run_until(60), Stop::Cycle);
+    assert_eq!(vm.x() * 60, 1140);
+}
+
+#[test]
+fn test_crt() {
+    const INPUT: &str = include_str!("../inputs/day10_test.txt");
+    let pixels = crt_pixels(parse(INPUT).unwrap());
+
+    assert_eq!(pixels.len(), 240);
+    assert!(render_crt(pixels.clone()).starts_with("##..##..##..##..##..##..##..##..##..##..\n"));
+    // the example draws a pattern, not letters
+    let err = read_crt(&pixels).unwrap_err();
+    assert_eq!(err.unrecognized.len(), 8);
+}
+
+#[test]
+fn test_instruction_set() {
+    let program = parse("addy 3\nmulx 5\naddx -1\njmp -1\nmuly -2").unwrap();
+    assert_eq!(program[3].to_string(), "jmp -1");
+
+    let mut vm = Vm::new(program);
+    vm.breakpoints = vec![Breakpoint::Register(Reg::X, -1)];
+    // addx and jmp loop until x reaches -1
+    assert_eq!(vm.run(), Stop::Breakpoint(Breakpoint::Register(Reg::X, -1)));
+    assert_eq!((vm.reg(Reg::Y), vm.cycle), (3, 23));
+    assert_eq!(vm.run_until(1000), Stop::Cycle);
+
+    let err = parse("noop\nsubx 3").unwrap_err();
+    assert_eq!((err.line, err.column, err.text.as_str()), (2, 1, "subx"));
+    assert_eq!(parse("noop 3").map_err(|e| e.column), Err(6));
+    assert_eq!(parse("jmp").map_err(|e| e.text), Err(String::from("jmp")));
+}
+
+#[test]
+fn test_limits() {
+    // loops forever, but only the screen's cycles are run
+    let forever = parse("noop\njmp 0").unwrap();
+    assert_eq!(solve1(forever.clone()), (20..=220).step_by(40).sum::<i64>());
+    assert_eq!(crt_pixels(forever.clone()).len(), 240);
+    assert_eq!(timeline(forever.clone(), 1000).len(), 1000);
+    assert_eq!(Vm::new(forever).run_until(1000), Stop::Cycle);
+
+    let mut vm = Vm::new(parse("addx 100000\nmulx 100000\njmp -1").unwrap());
+    assert_eq!(vm.run(), Stop::Overflow(Reg::X));
+    assert_eq!(vm.x(), 100001 * 100000_i64.pow(2));
+    assert_eq!(vm.run(), Stop::Overflow(Reg::X));
+
+    let mut vm = Vm::new(parse("noop\nnoop\njmp 9223372036854775807").unwrap());
+    assert_eq!(vm.run(), Stop::Halted);
+}
+
+#[test]
+fn test_query() {
+    const INPUT: &str = include_str!("../inputs/day10_test.txt");
+    let t = timeline(parse(INPUT).unwrap(), CRT_CYCLES);
+    assert_eq!(t.len(), 240);
+
+    let samples = query(&t, &[20, 220, 0, 241]);
+    assert_eq!(
+        samples[0].map(|s| (s.x(), s.signal_strength())),
+        Some((21, 420))
+    );
+    assert_eq!(samples[1].map(|s| s.signal_strength()), Some(3960));
+    assert_eq!(samples[2], None);
+    assert_eq!(samples[3], None);
+
+    assert_eq!(
+        to_csv(&t[..2]),
+        "cycle,x,y,signal_strength\n1,1,0,1\n2,1,0,2\n"
+    );
+}
+
+#[test]
+fn test_parse_cycles() {
+    assert_eq!(parse_cycles("20,60"), Ok(vec![20, 60]));
+    assert_eq!(parse_cycles("1-3,10"), Ok(vec![1, 2, 3, 10]));
+    assert_eq!(parse_cycles("20-100/40"), Ok(vec![20, 60, 100]));
+    assert_eq!(parse_cycles("20,6x").map_err(|e| e.column), Err(4));
+    assert!(parse_cycles("1-3/0").is_err());
+    let err = parse_cycles("20,100-20/5").unwrap_err();
+    assert_eq!((err.column, err.text.as_str()), (4, "100-20"));
+}
@@ -1,162 +1,929 @@
-struct Monkey<'a> {
-    items: Vec<i64>,
-    op: Box<dyn Fn(i64) -> i64 + 'a>,
-    test: Box<dyn Fn(i64) -> usize + 'a>,
-    inspects: i64,
+use crate::error::ParseError;
+use crate::solution::Solution;
+use nom::branch::alt;
+use nom::bytes::complete::tag;
+use nom::character::complete::{alpha1, char, i64, one_of, space0, space1, u64};
+use nom::combinator::{map, recognize, value};
+use nom::multi::{separated_list0, separated_list1};
+use nom::sequence::{preceded, terminated, tuple};
+use nom::IResult;
+use num::{BigInt, Zero};
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+
+// How a monkey changes the worry level of an item, e.g. "old * 19"
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Expr {
+    Old,
+    Num(i64),
+    Add(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
 }
 
-fn main() {
-    const INPUT: &str = include_str!("../inputs/day11.txt");
-    let parsed = parse(INPUT);
-    let solution1 = solve1(parsed);
+// Who a monkey throws an item to
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Test {
+    pub div_by: i64,
+    pub if_true: usize,
+    pub if_false: usize,
+}
 
-    let parsed2 = parse(INPUT);
-    let mod_by = get_mod(INPUT);
-    let solution2 = solve2(parsed2, mod_by);
+#[derive(Clone, Debug, PartialEq)]
+pub struct Monkey {
+    pub id: usize,
+    pub items: Vec<i64>,
+    pub op: Expr,
+    pub test: Test,
+    pub inspects: u64,
+}
 
-    println!("Solution 1: {:}", solution1);
-    println!("Solution 2: {:}", solution2);
+// A monkey inspecting an item and throwing it on, with the worry level
+// right after the operation and the one it ends up with after relief
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Throw<W = i64> {
+    pub round: u64,
+    pub from: usize,
+    pub after_op: W,
+    pub worry: W,
+    pub to: usize,
 }
 
-fn solve1(mut monkeys: Vec<Monkey>) -> i64 {
-    for _ in 0..20 {
-        play_round(&mut monkeys);
+// What happens to an item's worry level after a monkey's operation
+#[derive(Clone, Copy, Debug)]
+pub enum Relief {
+    None,
+    Divide(i64),
+    Modulo(i64),
+    Custom(fn(i64) -> i64),
+}
+
+pub struct Day11;
+
+impl Solution for Day11 {
+    type Input = Vec<Monkey>;
+    type Answer1 = u128;
+    type Answer2 = u128;
+
+    fn parse(input: &str) -> Result<Vec<Monkey>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(monkeys: &Vec<Monkey>) -> u128 {
+        solve1(monkeys.clone())
     }
 
-    let mut inspects: Vec<i64> = monkeys.iter().map(|m| m.inspects).collect();
+    fn part2(monkeys: &Vec<Monkey>) -> u128 {
+        solve2(monkeys.clone(), get_mod(monkeys))
+    }
+}
+
+// Exact worry levels are only needed if an i64 isn't enough, and dividing by
+// 3 can't fail with them
+pub fn solve1(monkeys: Vec<Monkey>) -> u128 {
+    let relief = Relief::Divide(3);
+    let inspects = checked_inspections(&monkeys, 20, relief)
+        .or_else(|_| exact_inspections(&monkeys, 20, relief))
+        .unwrap();
+
+    monkey_business(&inspects)
+}
+
+pub fn solve2(monkeys: Vec<Monkey>, mod_by: i64) -> u128 {
+    monkey_business(&inspections_after(&monkeys, mod_by, 10000).unwrap())
+}
+
+// The product of the two highest inspection counts, or just the highest if
+// there's only one monkey
+pub fn monkey_business(inspects: &[u64]) -> u128 {
+    let mut inspects = inspects.to_vec();
     inspects.sort_by(|a, b| b.cmp(a));
 
-    inspects[0] * inspects[1]
+    inspects.iter().take(2).map(|&i| i as u128).product()
 }
 
-fn solve2(mut monkeys: Vec<Monkey>, mod_by: i64) -> i64 {
-    for _ in 0..10000 {
-        play_round2(&mut monkeys, mod_by);
+// How many items each monkey inspects in `rounds` rounds, with the worry
+// levels kept modulo `mod_by`. Every item moves on its own, so each is
+// followed until its (holder, worry) state at the start of a round repeats
+// and the rest of the rounds are extrapolated from the cycle. Falls back to
+// exact arithmetic when a worry level below `mod_by` doesn't fit in an i64
+// after an operation.
+pub fn inspections_after(
+    monkeys: &[Monkey],
+    mod_by: i64,
+    rounds: u64,
+) -> Result<Vec<u64>, RoundError> {
+    modular_inspections::<i64>(monkeys, mod_by, rounds)
+        .or_else(|_| modular_inspections::<BigInt>(monkeys, mod_by, rounds))
+}
+
+fn modular_inspections<W: Worry + Eq + Hash>(
+    monkeys: &[Monkey],
+    mod_by: i64,
+    rounds: u64,
+) -> Result<Vec<u64>, RoundError> {
+    let relief = Relief::Modulo(mod_by);
+    check_relief(relief)?;
+
+    let mut inspects = vec![0; monkeys.len()];
+    for (holder, m) in monkeys.iter().enumerate() {
+        for &worry in m.items.iter() {
+            let state = (holder, W::from_i64(worry % mod_by));
+            let counts = item_inspections(monkeys, relief, state, rounds)?;
+            for (total, count) in inspects.iter_mut().zip(counts) {
+                *total += count;
+            }
+        }
     }
 
-    let mut inspects: Vec<i64> = monkeys.iter().map(|m| m.inspects).collect();
-    inspects.sort_by(|a, b| b.cmp(a));
+    Ok(inspects)
+}
+
+fn item_inspections<W: Worry + Eq + Hash>(
+    monkeys: &[Monkey],
+    relief: Relief,
+    mut state: (usize, W),
+    rounds: u64,
+) -> Result<Vec<u64>, RoundError> {
+    // The round each state was first seen in, and who inspected the item in
+    // each round
+    let mut seen: HashMap<(usize, W), u64> = HashMap::new();
+    let mut history: Vec<Vec<usize>> = vec![];
+
+    let mut round = 0;
+    while round < rounds {
+        if let Some(&start) = seen.get(&state) {
+            return Ok(extrapolate(monkeys.len(), &history, start, rounds));
+        }
+        seen.insert(state.clone(), round);
+
+        let mut throws = vec![];
+        round += 1;
+        state = item_round(monkeys, relief, round, state, &mut throws)?;
+        history.push(throws.iter().map(|t| t.from).collect());
+    }
 
-    inspects[0] * inspects[1]
+    let mut counts = vec![0; monkeys.len()];
+    for i in history.iter().flatten() {
+        counts[*i] += 1;
+    }
+    Ok(counts)
 }
 
-fn parse(s: &str) -> Vec<Monkey> {
-    s.split("\n\n").map(parse_monkey).collect()
+// Move an item through one round, with `relief` applied to its worry level
+// after each operation. It's inspected again in the same round whenever
+// it's thrown to a monkey that hasn't had its turn yet.
+fn item_round<W: Worry>(
+    monkeys: &[Monkey],
+    relief: Relief,
+    round: u64,
+    (mut holder, mut worry): (usize, W),
+    throws: &mut Vec<Throw<W>>,
+) -> Result<(usize, W), RoundError> {
+    loop {
+        let (after_op, relieved) = inspect(monkeys, holder, &worry, relief, round)?;
+        worry = relieved;
+        let to = monkeys[holder].test.target(&worry);
+        throws.push(Throw {
+            round,
+            from: holder,
+            after_op,
+            worry: worry.clone(),
+            to,
+        });
+        if to <= holder {
+            return Ok((to, worry));
+        }
+        holder = to;
+    }
 }
 
-fn parse_monkey(s: &str) -> Monkey {
-    let ls: Vec<&str> = s.lines().collect();
-    let items: Vec<i64> = ls[1][18..]
-        .split(", ")
-        .map(|str| str.parse().unwrap())
-        .collect();
-    let op: Box<dyn Fn(i64) -> i64> = parse_operation(ls[2]);
-    let test_lines: Vec<&str> = ls[3..6].to_vec();
-    let test = parse_test(&test_lines);
+// Follow the `item`th item held by `monkey` at the start for `rounds`
+// rounds. None if there's no such item.
+pub fn trace_item(
+    monkeys: &[Monkey],
+    monkey: usize,
+    item: usize,
+    rounds: u64,
+    relief: Relief,
+) -> Result<Option<Vec<Throw>>, RoundError> {
+    check_relief(relief)?;
+    let start = monkeys.get(monkey).and_then(|m| m.items.get(item));
+    let mut state = match start {
+        Some(&worry) => (monkey, worry),
+        None => return Ok(None),
+    };
 
-    Monkey {
-        items,
-        op,
-        test,
-        inspects: 0,
+    let mut throws = vec![];
+    for round in 1..=rounds {
+        state = item_round(monkeys, relief, round, state, &mut throws)?;
     }
+
+    Ok(Some(throws))
 }
 
-fn parse_operation(s: &str) -> Box<dyn Fn(i64) -> i64 + '_> {
-    let op_parts: Vec<&str> = s[19..].split(' ').collect();
+// The inspections over `rounds` rounds when the rounds from `start` to the
+// end of `history` repeat forever
+fn extrapolate(monkeys: usize, history: &[Vec<usize>], start: u64, rounds: u64) -> Vec<u64> {
+    let start = start as usize;
+    let cycle = (history.len() - start) as u64;
+    let repeats = (rounds - start as u64) / cycle;
+    let rest = ((rounds - start as u64) % cycle) as usize;
+
+    let mut counts = vec![0; monkeys];
+    for (round, inspected_by) in history.iter().enumerate() {
+        let times = if round < start {
+            1
+        } else if round - start < rest {
+            repeats + 1
+        } else {
+            repeats
+        };
+        for i in inspected_by {
+            counts[*i] += times;
+        }
+    }
+    counts
+}
 
-    let var1 = parse_num(op_parts[0]);
-    let var2 = parse_num(op_parts[2]);
-    let func = parse_numop(op_parts[1]);
+// One line of notes about a monkey, after its label
+#[derive(Clone, Debug, PartialEq)]
+enum Note {
+    Items(Vec<i64>),
+    Operation(Expr),
+    Divisor(i64),
+    IfTrue(usize),
+    IfFalse(usize),
+}
 
-    Box::new(move |a| func(var1(a), var2(a)))
+// The notes about one monkey, in any order, with the lines they're on
+struct Block<'a> {
+    id: usize,
+    header: (usize, &'a str),
+    notes: Vec<(usize, &'a str, Note)>,
 }
 
-fn parse_numop(s: &str) -> fn(i64, i64) -> i64 {
-    match s {
-        "+" => |a, b| a + b,
-        "*" => |a, b| a * b,
-        _ => panic!("Failed to parse operation \"{:}\"", s),
+// Monkeys start with a "Monkey N:" line followed by their notes. Extra
+// whitespace and blank lines are allowed and the notes can come in any
+// order, but each has to be there once.
+pub fn parse(s: &str) -> Result<Vec<Monkey>, ParseError> {
+    let mut blocks: Vec<Block> = vec![];
+    for (i, line) in s.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if line.trim_start().starts_with("Monkey") {
+            let id = parse_header(line).map_err(|e| e.offset(i))?;
+            blocks.push(Block {
+                id,
+                header: (i, line),
+                notes: vec![],
+            });
+        } else {
+            let block = blocks.last_mut().ok_or_else(|| {
+                ParseError::new(line, line.trim(), "expected \"Monkey N:\" before").offset(i)
+            })?;
+            let note = parse_note(line).map_err(|e| in_monkey(e.offset(i), block.id))?;
+            block.notes.push((i, line, note));
+        }
+    }
+    if blocks.is_empty() {
+        return Err(ParseError::new(s, "", "expected \"Monkey 0:\""));
+    }
+
+    let monkeys = blocks
+        .iter()
+        .enumerate()
+        .map(|(i, block)| block.monkey(i))
+        .collect::<Result<Vec<Monkey>, ParseError>>()?;
+
+    // Every monkey has to throw to another one that exists, and test with a
+    // divisor that isn't 0
+    for block in blocks.iter() {
+        for (i, line, note) in block.notes.iter() {
+            let problem = match note {
+                Note::IfTrue(to) | Note::IfFalse(to) if *to >= monkeys.len() => "there's no monkey",
+                Note::IfTrue(to) | Note::IfFalse(to) if *to == block.id => {
+                    "can't throw to itself, found monkey"
+                }
+                Note::Divisor(0) => "expected a divisor other than 0, found",
+                _ => continue,
+            };
+            let text = line.split_whitespace().last().unwrap_or(line);
+            let err = ParseError::new(line, text, problem);
+            return Err(in_monkey(err.offset(*i), block.id));
+        }
     }
+
+    Ok(monkeys)
 }
 
-fn parse_num(s: &'_ str) -> Box<dyn Fn(i64) -> i64 + '_> {
-    match s {
-        "old" => Box::new(|a| a),
-        num => Box::new(|_| num.parse().unwrap()),
+impl<'a> Block<'a> {
+    // The monkey at `index`, which has to be its number too since that's
+    // how the others throw to it
+    fn monkey(&self, index: usize) -> Result<Monkey, ParseError> {
+        let (line, header) = self.header;
+        if self.id != index {
+            let err = ParseError::new(
+                header,
+                header.trim(),
+                &format!("expected monkey {:}, found", index),
+            );
+            return Err(err.offset(line));
+        }
+
+        Ok(Monkey {
+            id: self.id,
+            items: self.find("Starting items", |n| match n {
+                Note::Items(items) => Some(items.clone()),
+                _ => None,
+            })?,
+            op: self.find("Operation", |n| match n {
+                Note::Operation(op) => Some(op.clone()),
+                _ => None,
+            })?,
+            test: Test {
+                div_by: self.find("Test", |n| match n {
+                    Note::Divisor(d) => Some(*d),
+                    _ => None,
+                })?,
+                if_true: self.find("If true", |n| match n {
+                    Note::IfTrue(to) => Some(*to),
+                    _ => None,
+                })?,
+                if_false: self.find("If false", |n| match n {
+                    Note::IfFalse(to) => Some(*to),
+                    _ => None,
+                })?,
+            },
+            inspects: 0,
+        })
+    }
+
+    // The one note `f` picks out, called `label` in the input
+    fn find<T, F: Fn(&Note) -> Option<T>>(&self, label: &str, f: F) -> Result<T, ParseError> {
+        let mut found = self
+            .notes
+            .iter()
+            .filter_map(|(i, line, note)| f(note).map(|value| (i, line, value)));
+
+        let (_, _, value) = found.next().ok_or_else(|| {
+            let (i, header) = self.header;
+            let message = format!("missing the \"{:}\" note after", label);
+            in_monkey(
+                ParseError::new(header, header.trim(), &message).offset(i),
+                self.id,
+            )
+        })?;
+        if let Some((i, line, _)) = found.next() {
+            let message = format!("a second \"{:}\" note", label);
+            return Err(in_monkey(
+                ParseError::new(line, line.trim(), &message).offset(*i),
+                self.id,
+            ));
+        }
+
+        Ok(value)
+    }
+}
+
+// Say which monkey an error is about
+fn in_monkey(mut err: ParseError, id: usize) -> ParseError {
+    err.message = format!("monkey {:}: {:}", id, err.message);
+    err
+}
+
+// Turn the result of parsing `line` with nom into a ParseError pointing at
+// the first word it couldn't make sense of
+fn finish<'a, T>(line: &'a str, result: IResult<&'a str, T>, what: &str) -> Result<T, ParseError> {
+    let unexpected = |rest: &'a str| {
+        let rest = rest.trim_start();
+        let word = rest.split_whitespace().next().unwrap_or(rest);
+        ParseError::new(line, word, &format!("expected {:}, found", what))
+    };
+
+    match result {
+        Ok((rest, value)) if rest.trim().is_empty() => Ok(value),
+        Ok((rest, _)) => Err(unexpected(rest)),
+        Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => Err(unexpected(e.input)),
+        Err(nom::Err::Incomplete(_)) => Err(unexpected("")),
+    }
+}
+
+fn parse_header(line: &str) -> Result<usize, ParseError> {
+    let header = tuple((tag("Monkey"), space1, u64, space0, char(':')));
+    let result = map(header, |(_, _, id, _, _)| id as usize)(line.trim_start());
+    finish(line, result, "\"Monkey N:\"")
+}
+
+fn parse_note(line: &str) -> Result<Note, ParseError> {
+    let label = recognize(separated_list1(space1, alpha1));
+    let (rest, label) = terminated(label, tuple((space0, char(':'), space0)))(line.trim_start())
+        .map_err(|_: nom::Err<nom::error::Error<&str>>| {
+            ParseError::new(line, line.trim(), "expected a note, found")
+        })?;
+
+    let to_monkey = || {
+        map(
+            tuple((
+                tag("throw"),
+                space1,
+                tag("to"),
+                space1,
+                tag("monkey"),
+                space1,
+                u64,
+            )),
+            |(_, _, _, _, _, _, to)| to as usize,
+        )
+    };
+    match label
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .as_str()
+    {
+        "Starting items" => {
+            let items = separated_list0(tuple((space0, char(','), space0)), i64);
+            finish(line, map(items, Note::Items)(rest), "a list of items")
+        }
+        "Operation" => finish(line, map(operation, Note::Operation)(rest), "an operation"),
+        "Test" => {
+            let divisor = preceded(tuple((tag("divisible"), space1, tag("by"), space1)), i64);
+            finish(
+                line,
+                map(divisor, Note::Divisor)(rest),
+                "\"divisible by N\"",
+            )
+        }
+        "If true" => finish(
+            line,
+            map(to_monkey(), Note::IfTrue)(rest),
+            "\"throw to monkey N\"",
+        ),
+        "If false" => finish(
+            line,
+            map(to_monkey(), Note::IfFalse)(rest),
+            "\"throw to monkey N\"",
+        ),
+        _ => Err(ParseError::new(line, label, "unknown note")),
     }
 }
 
-fn parse_test<'a>(s: &'_ [&str]) -> Box<dyn Fn(i64) -> usize + 'a> {
-    let div_by: i64 = s[0].split(' ').last().unwrap().parse().unwrap();
-    let true_target: usize = s[1].split(' ').last().unwrap().parse().unwrap();
-    let false_target: usize = s[2].split(' ').last().unwrap().parse().unwrap();
+// "new = old * 19"
+fn operation(s: &str) -> IResult<&str, Expr> {
+    let operand = || alt((value(Expr::Old, tag("old")), map(i64, Expr::Num)));
+    let (s, (_, _, _, _, lhs, _, op, _, rhs)) = tuple((
+        tag("new"),
+        space0,
+        char('='),
+        space0,
+        operand(),
+        space0,
+        one_of("+*"),
+        space0,
+        operand(),
+    ))(s)?;
+
+    let (lhs, rhs) = (Box::new(lhs), Box::new(rhs));
+    match op {
+        '+' => Ok((s, Expr::Add(lhs, rhs))),
+        _ => Ok((s, Expr::Mul(lhs, rhs))),
+    }
+}
+
+impl Expr {
+    pub fn eval(&self, old: i64) -> i64 {
+        match self {
+            Expr::Old => old,
+            Expr::Num(n) => *n,
+            Expr::Add(a, b) => a.eval(old) + b.eval(old),
+            Expr::Mul(a, b) => a.eval(old) * b.eval(old),
+        }
+    }
+
+    // None if the worry level doesn't fit in an i64
+    pub fn checked_eval(&self, old: i64) -> Option<i64> {
+        match self {
+            Expr::Old => Some(old),
+            Expr::Num(n) => Some(*n),
+            Expr::Add(a, b) => a.checked_eval(old)?.checked_add(b.checked_eval(old)?),
+            Expr::Mul(a, b) => a.checked_eval(old)?.checked_mul(b.checked_eval(old)?),
+        }
+    }
+
+    pub fn eval_big(&self, old: &BigInt) -> BigInt {
+        match self {
+            Expr::Old => old.clone(),
+            Expr::Num(n) => BigInt::from(*n),
+            Expr::Add(a, b) => a.eval_big(old) + b.eval_big(old),
+            Expr::Mul(a, b) => a.eval_big(old) * b.eval_big(old),
+        }
+    }
+}
 
-    Box::new(move |a| {
-        if a % div_by == 0 {
-            true_target
+impl Test {
+    pub fn target<W: Worry>(&self, worry: &W) -> usize {
+        if worry.divisible_by(self.div_by) {
+            self.if_true
         } else {
-            false_target
+            self.if_false
         }
-    })
+    }
 }
 
-fn play_round(monkeys: &mut Vec<Monkey>) {
-    for i in 0..monkeys.len() {
-        for j in 0..monkeys[i].items.len() {
-            let new_worry = (monkeys[i].op)(monkeys[i].items[j]) / 3;
-            let throw_to = (monkeys[i].test)(new_worry);
-            monkeys[throw_to].items.push(new_worry);
-            monkeys[i].inspects += 1;
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expr::Old => write!(f, "old"),
+            Expr::Num(n) => write!(f, "{:}", n),
+            Expr::Add(a, b) => write!(f, "{:} + {:}", a, b),
+            Expr::Mul(a, b) => write!(f, "{:} * {:}", a, b),
         }
-        monkeys[i].items = vec![];
     }
 }
 
-fn play_round2(monkeys: &mut Vec<Monkey>, mod_by: i64) {
-    for i in 0..monkeys.len() {
-        for j in 0..monkeys[i].items.len() {
-            let new_worry = (monkeys[i].op)(monkeys[i].items[j]) % mod_by;
-            let throw_to = (monkeys[i].test)(new_worry);
-            monkeys[throw_to].items.push(new_worry);
-            monkeys[i].inspects += 1;
+// In the same format as the input
+impl fmt::Display for Monkey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let items: Vec<String> = self.items.iter().map(|i| i.to_string()).collect();
+        writeln!(f, "Monkey {:}:", self.id)?;
+        match items.is_empty() {
+            true => writeln!(f, "  Starting items:")?,
+            false => writeln!(f, "  Starting items: {:}", items.join(", "))?,
         }
-        monkeys[i].items = vec![];
+        writeln!(f, "  Operation: new = {:}", self.op)?;
+        writeln!(f, "  Test: divisible by {:}", self.test.div_by)?;
+        writeln!(f, "    If true: throw to monkey {:}", self.test.if_true)?;
+        write!(f, "    If false: throw to monkey {:}", self.test.if_false)
+    }
+}
+
+impl<W: fmt::Display> fmt::Display for Throw<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Round {:}: monkey {:} sets the worry level to {:}, then {:}, and throws to monkey {:}",
+            self.round, self.from, self.after_op, self.worry, self.to
+        )
     }
 }
 
-fn get_mod(s: &str) -> i64 {
-    let monkeys_s = s.split("\n\n");
-    let mut ret = 1;
-    for ms in monkeys_s {
-        let div_line: &str = ms.lines().nth(3).unwrap();
-        let div: i64 = div_line[21..].parse().unwrap();
-        ret *= div;
+pub fn format_monkeys(monkeys: &[Monkey]) -> String {
+    let blocks: Vec<String> = monkeys.iter().map(|m| m.to_string()).collect();
+    blocks.join("\n\n") + "\n"
+}
+
+impl Relief {
+    // Keep the worry levels below the least common multiple of the
+    // divisors, which leaves every test's outcome intact
+    pub fn lcm(monkeys: &[Monkey]) -> Relief {
+        Relief::Modulo(get_mod(monkeys))
+    }
+
+    pub fn apply(self, worry: i64) -> i64 {
+        match self {
+            Relief::None => worry,
+            Relief::Divide(n) => worry / n,
+            Relief::Modulo(n) => worry % n,
+            Relief::Custom(f) => f(worry),
+        }
     }
-    ret
+}
+
+// A type the worry levels can be kept as while playing the rounds
+pub trait Worry: Clone + fmt::Display {
+    fn from_i64(n: i64) -> Self;
+
+    // The worry level after an operation, None if it doesn't fit
+    fn operate(&self, op: &Expr) -> Option<Self>;
+
+    // The worry level after relief, None if it doesn't fit
+    fn relieve(self, relief: Relief) -> Option<Self>;
+
+    fn divisible_by(&self, n: i64) -> bool;
+}
+
+impl Worry for i64 {
+    fn from_i64(n: i64) -> i64 {
+        n
+    }
+
+    fn operate(&self, op: &Expr) -> Option<i64> {
+        op.checked_eval(*self)
+    }
+
+    fn relieve(self, relief: Relief) -> Option<i64> {
+        Some(relief.apply(self))
+    }
+
+    fn divisible_by(&self, n: i64) -> bool {
+        self % n == 0
+    }
+}
+
+// Exact, to check the shortcuts against. A custom relief only works while
+// the worry level fits in an i64.
+impl Worry for BigInt {
+    fn from_i64(n: i64) -> BigInt {
+        BigInt::from(n)
+    }
+
+    fn operate(&self, op: &Expr) -> Option<BigInt> {
+        Some(op.eval_big(self))
+    }
+
+    fn relieve(self, relief: Relief) -> Option<BigInt> {
+        match relief {
+            Relief::None => Some(self),
+            Relief::Divide(n) => Some(self / n),
+            Relief::Modulo(n) => Some(self % n),
+            Relief::Custom(f) => i64::try_from(&self).ok().map(|w| BigInt::from(f(w))),
+        }
+    }
+
+    fn divisible_by(&self, n: i64) -> bool {
+        (self % n).is_zero()
+    }
+}
+
+// Why the rounds couldn't be played
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RoundError {
+    // a worry level got too big for its type
+    Overflow {
+        round: u64,
+        monkey: usize,
+        worry: String,
+    },
+    // dividing by 0 or taking the modulus by 0
+    ZeroRelief,
+}
+
+impl fmt::Display for RoundError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RoundError::Overflow {
+                round,
+                monkey,
+                worry,
+            } => write!(
+                f,
+                "The worry level overflowed in round {:} when monkey {:} inspected an item at {:}",
+                round, monkey, worry
+            ),
+            RoundError::ZeroRelief => write!(f, "The worry levels can't be relieved by 0"),
+        }
+    }
+}
+
+impl std::error::Error for RoundError {}
+
+// Play `rounds` rounds, moving the items between the monkeys' lists in
+// `items`, and count how many items each monkey inspects
+pub fn play<W: Worry>(
+    monkeys: &[Monkey],
+    items: &mut [Vec<W>],
+    rounds: u64,
+    relief: Relief,
+) -> Result<Vec<u64>, RoundError> {
+    check_relief(relief)?;
+
+    let mut inspects = vec![0; monkeys.len()];
+    for round in 1..=rounds {
+        for (i, m) in monkeys.iter().enumerate() {
+            for worry in std::mem::take(&mut items[i]) {
+                let (_, new_worry) = inspect(monkeys, i, &worry, relief, round)?;
+                items[m.test.target(&new_worry)].push(new_worry);
+                inspects[i] += 1;
+            }
+        }
+    }
+
+    Ok(inspects)
+}
+
+fn check_relief(relief: Relief) -> Result<(), RoundError> {
+    match relief {
+        Relief::Divide(0) | Relief::Modulo(0) => Err(RoundError::ZeroRelief),
+        _ => Ok(()),
+    }
+}
+
+// Monkey `i` inspecting an item. Returns its worry level right after the
+// operation and after relief.
+fn inspect<W: Worry>(
+    monkeys: &[Monkey],
+    i: usize,
+    worry: &W,
+    relief: Relief,
+    round: u64,
+) -> Result<(W, W), RoundError> {
+    let overflow = || RoundError::Overflow {
+        round,
+        monkey: i,
+        worry: worry.to_string(),
+    };
+    let after_op = worry.operate(&monkeys[i].op).ok_or_else(overflow)?;
+    let relieved = after_op.clone().relieve(relief).ok_or_else(overflow)?;
+
+    Ok((after_op, relieved))
+}
+
+// The monkeys' starting items as another worry type
+fn items_as<W: Worry>(monkeys: &[Monkey]) -> Vec<Vec<W>> {
+    monkeys
+        .iter()
+        .map(|m| m.items.iter().map(|&i| W::from_i64(i)).collect())
+        .collect()
+}
+
+pub fn play_round(monkeys: &mut [Monkey], relief: Relief) -> Result<(), RoundError> {
+    play_rounds(monkeys, 1, relief)
+}
+
+// Play on the monkeys themselves, leaving them with the items they hold
+// afterwards and their inspections added up
+pub fn play_rounds(monkeys: &mut [Monkey], rounds: u64, relief: Relief) -> Result<(), RoundError> {
+    let mut items: Vec<Vec<i64>> = monkeys
+        .iter_mut()
+        .map(|m| std::mem::take(&mut m.items))
+        .collect();
+    let inspects = play(monkeys, &mut items, rounds, relief);
+
+    for (m, items) in monkeys.iter_mut().zip(items) {
+        m.items = items;
+    }
+    for (m, n) in monkeys.iter_mut().zip(inspects?) {
+        m.inspects += n;
+    }
+    Ok(())
+}
+
+// How many items each monkey inspects in `rounds` rounds, failing instead
+// of wrapping when the worry levels get too big
+pub fn checked_inspections(
+    monkeys: &[Monkey],
+    rounds: u64,
+    relief: Relief,
+) -> Result<Vec<u64>, RoundError> {
+    play(monkeys, &mut items_as::<i64>(monkeys), rounds, relief)
+}
+
+// The same with exact worry levels. The numbers grow quickly without
+// relief, so this is only for a few rounds.
+pub fn exact_inspections(
+    monkeys: &[Monkey],
+    rounds: u64,
+    relief: Relief,
+) -> Result<Vec<u64>, RoundError> {
+    play(monkeys, &mut items_as::<BigInt>(monkeys), rounds, relief)
+}
+
+// The least common multiple of the divisors, which keeps the tests intact
+// when taken as a modulus of the worry levels
+pub fn get_mod(monkeys: &[Monkey]) -> i64 {
+    monkeys
+        .iter()
+        .map(|m| m.test.div_by)
+        .fold(1, num::integer::lcm)
 }
 
 #[test]
 fn test_monkey_operation() {
-    let op = parse_operation("  Operation: new = old * 19");
-    assert_eq!(op(2), 2 * 19);
+    let op = match parse_note("  Operation: new = old * 19") {
+        Ok(Note::Operation(op)) => op,
+        note => panic!("expected an operation, got {:?}", note),
+    };
+    assert_eq!(op, Expr::Mul(Box::new(Expr::Old), Box::new(Expr::Num(19))));
+    assert_eq!(op.eval(2), 2 * 19);
+    assert_eq!(op.to_string(), "old * 19");
 }
 
 #[test]
 fn test_monkey_test() {
-    let test = parse_test(&[
-        "  Test: divisible by 23",
-        "    If true: throw to monkey 2",
-        "    If false: throw to monkey 3",
-    ]);
-    assert_eq!(test(46), 2);
-    assert_eq!(test(44), 3);
+    const INPUT: &str = include_str!("../inputs/day11_test.txt");
+    let test = parse(INPUT).unwrap()[0].test.clone();
+    assert_eq!(test.target(&46), 2);
+    assert_eq!(test.target(&44), 3);
+    assert_eq!(test.target(&BigInt::from(46)), 2);
+}
+
+#[test]
+fn test_parse_errors() {
+    const INPUT: &str = include_str!("../inputs/day11_test.txt");
+
+    let broken = INPUT.replacen("Operation: new = old + 6", "Operation: new = old - 6", 1);
+    let err = parse(&broken).unwrap_err();
+    assert_eq!((err.line, err.column, err.text.as_str()), (10, 24, "-"));
+
+    let broken = INPUT.replacen("If true: throw to monkey 2", "If true: throw to 2", 1);
+    assert_eq!(parse(&broken).map_err(|e| e.line), Err(5));
+
+    let broken = INPUT.replacen("Monkey 1:", "Monkey 1", 1);
+    assert_eq!(parse(&broken).map_err(|e| e.line), Err(8));
+
+    for empty in ["", "  \n\n"] {
+        assert_eq!(
+            parse(empty).map_err(|e| e.to_string()),
+            Err(String::from("line 1, column 1: expected \"Monkey 0:\""))
+        );
+    }
+
+    let broken = INPUT.replacen(
+        "If false: throw to monkey 3",
+        "If false: throw to monkey 4",
+        1,
+    );
+    let err = parse(&broken).unwrap_err();
+    assert_eq!((err.line, err.text.as_str()), (6, "4"));
+    assert_eq!(
+        err.to_string(),
+        "line 6, column 31: monkey 0: there's no monkey \"4\""
+    );
+
+    let broken = INPUT.replacen("divisible by 19", "divisible by 0", 1);
+    assert_eq!(
+        parse(&broken).map_err(|e| e.to_string()),
+        Err(String::from(
+            "line 11, column 22: monkey 1: expected a divisor other than 0, found \"0\""
+        ))
+    );
+
+    let broken = INPUT.replacen(
+        "If true: throw to monkey 2",
+        "If true: throw to monkey 0",
+        1,
+    );
+    assert_eq!(
+        parse(&broken).map_err(|e| e.to_string()),
+        Err(String::from(
+            "line 5, column 30: monkey 0: can't throw to itself, found monkey \"0\""
+        ))
+    );
+
+    let broken = INPUT.replacen("  Test: divisible by 19\n", "", 1);
+    let err = parse(&broken).unwrap_err();
+    assert_eq!(err.line, 8);
+    assert_eq!(err.message, "monkey 1: missing the \"Test\" note after");
+
+    let broken = INPUT.replacen(
+        "Operation: new = old * old",
+        "Operation: new = old * 19\n  Operation: new = old * old",
+        1,
+    );
+    assert_eq!(parse(&broken).map_err(|e| e.line), Err(18));
+
+    let broken = INPUT.replacen("Monkey 2:", "Monkey 3:", 1);
+    assert_eq!(
+        parse(&broken).map_err(|e| e.message),
+        Err(String::from("expected monkey 2, found"))
+    );
+}
+
+#[test]
+fn test_parse_variations() {
+    const INPUT: &str = include_str!("../inputs/day11_test.txt");
+    let parsed = parse(INPUT).unwrap();
+
+    let spaced = "\n\n Monkey   0 :\n\tIf false:throw to  monkey 3\n  Starting items:79 ,98  \n\n  If true: throw to monkey 2\n Test :  divisible by 23\nOperation: new=old*19\n";
+    let monkeys = parse(&INPUT.replacen(&format_monkeys(&parsed[..1]), spaced, 1)).unwrap();
+    assert_eq!(monkeys, parsed);
+
+    let mut empty = parsed.clone();
+    empty[3].items = vec![];
+    let input = INPUT.replacen("Starting items: 74", "Starting items:", 1);
+    assert_eq!(parse(&input), Ok(empty));
+}
+
+#[test]
+fn test_format() {
+    const INPUT: &str = include_str!("../inputs/day11_test.txt");
+    let parsed = parse(INPUT).unwrap();
+
+    assert_eq!(format_monkeys(&parsed), INPUT);
+    assert_eq!(parse(&format_monkeys(&parsed)), Ok(parsed.clone()));
+
+    let mut empty = parsed.clone();
+    empty[0].items = vec![];
+    assert_eq!(parse(&format_monkeys(&empty)), Ok(empty));
+}
+
+#[test]
+fn test_monkey_business() {
+    assert_eq!(monkey_business(&[3, 7, 5]), 35);
+    assert_eq!(monkey_business(&[7]), 7);
+    assert_eq!(
+        monkey_business(&[u64::MAX, u64::MAX]),
+        u64::MAX as u128 * u64::MAX as u128
+    );
 }
 
 #[test]
 fn test_solve1() {
     const INPUT: &str = include_str!("../inputs/day11_test.txt");
-    let parsed = parse(INPUT);
+    let parsed = parse(INPUT).unwrap();
     let solution1 = solve1(parsed);
     assert_eq!(solution1, 10605);
 }
@@ -164,8 +931,193 @@ fn test_solve1() {
 #[test]
 fn test_solve2() {
     const INPUT: &str = include_str!("../inputs/day11_test.txt");
-    let parsed = parse(INPUT);
-    let mod_by = get_mod(INPUT);
+    let parsed = parse(INPUT).unwrap();
+    let mod_by = get_mod(&parsed);
     let solution2 = solve2(parsed, mod_by);
     assert_eq!(solution2, 2713310158);
 }
+
+#[test]
+fn test_inspections_after() {
+    const INPUT: &str = include_str!("../inputs/day11_test.txt");
+    let parsed = parse(INPUT).unwrap();
+    let mod_by = get_mod(&parsed);
+
+    let mut monkeys = parsed.clone();
+    for round in 1..=1000 {
+        play_round(&mut monkeys, Relief::Modulo(mod_by)).unwrap();
+        if [1, 20, 1000].contains(&round) {
+            let inspects: Vec<u64> = monkeys.iter().map(|m| m.inspects).collect();
+            assert_eq!(inspections_after(&parsed, mod_by, round), Ok(inspects));
+        }
+    }
+    assert_eq!(
+        inspections_after(&parsed, mod_by, 1000),
+        Ok(vec![5204, 4792, 199, 5192])
+    );
+
+    let huge = inspections_after(&parsed, mod_by, 1_000_000_000_000).unwrap();
+    assert!(monkey_business(&huge) > u64::MAX as u128);
+    assert_eq!(
+        inspections_after(&parsed, 0, 20),
+        Err(RoundError::ZeroRelief)
+    );
+}
+
+#[test]
+fn test_inspections_after_overflow() {
+    // The worry levels stay below 2 * 4000000007, but squaring them doesn't
+    // fit in an i64
+    const INPUT: &str = "Monkey 0:
+  Starting items: 7999999999, 4000000008
+  Operation: new = old * old
+  Test: divisible by 4000000007
+    If true: throw to monkey 1
+    If false: throw to monkey 1
+
+Monkey 1:
+  Starting items: 3
+  Operation: new = old + 1
+  Test: divisible by 2
+    If true: throw to monkey 0
+    If false: throw to monkey 0
+";
+    let parsed = parse(INPUT).unwrap();
+    let mod_by = get_mod(&parsed);
+    assert_eq!(mod_by, 8000000014);
+    assert!(matches!(
+        checked_inspections(&parsed, 1, Relief::Modulo(mod_by)),
+        Err(RoundError::Overflow { .. })
+    ));
+
+    for rounds in [1, 2, 50] {
+        assert_eq!(
+            inspections_after(&parsed, mod_by, rounds),
+            exact_inspections(&parsed, rounds, Relief::Modulo(mod_by))
+        );
+    }
+    // Every item goes to monkey 1 and back each round, except the one
+    // monkey 1 starts with skips monkey 0 in the first round
+    assert_eq!(solve2(parsed, mod_by), 29999 * 30000);
+}
+
+#[test]
+fn test_trace_item() {
+    const INPUT: &str = include_str!("../inputs/day11_test.txt");
+    let parsed = parse(INPUT).unwrap();
+
+    let trace = trace_item(&parsed, 0, 0, 1, Relief::Divide(3))
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        trace,
+        [
+            Throw {
+                round: 1,
+                from: 0,
+                after_op: 1501,
+                worry: 500,
+                to: 3
+            },
+            Throw {
+                round: 1,
+                from: 3,
+                after_op: 503,
+                worry: 167,
+                to: 1
+            },
+        ]
+    );
+    assert_eq!(
+        trace[0].to_string(),
+        "Round 1: monkey 0 sets the worry level to 1501, then 500, and throws to monkey 3"
+    );
+    assert_eq!(trace_item(&parsed, 0, 2, 1, Relief::Divide(3)), Ok(None));
+    assert_eq!(
+        trace_item(&parsed, 0, 0, 1, Relief::Divide(0)),
+        Err(RoundError::ZeroRelief)
+    );
+    match trace_item(&parsed, 1, 0, 20, Relief::None) {
+        Err(RoundError::Overflow { monkey, .. }) => assert_eq!(monkey, 2),
+        result => panic!("expected an overflow, got {:?}", result),
+    }
+
+    // Every inspection shows up in the trace of one of the items
+    let mut monkeys = parsed.clone();
+    play_rounds(&mut monkeys, 20, Relief::Divide(3)).unwrap();
+    let mut inspects = vec![0; parsed.len()];
+    for (i, m) in parsed.iter().enumerate() {
+        for j in 0..m.items.len() {
+            let trace = trace_item(&parsed, i, j, 20, Relief::Divide(3));
+            for throw in trace.unwrap().unwrap() {
+                inspects[throw.from] += 1;
+            }
+        }
+    }
+    assert_eq!(
+        inspects,
+        monkeys.iter().map(|m| m.inspects).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_exact_inspections() {
+    const INPUT: &str = include_str!("../inputs/day11_test.txt");
+    let parsed = parse(INPUT).unwrap();
+    let mod_by = get_mod(&parsed);
+
+    let mut monkeys = parsed.clone();
+    play_rounds(&mut monkeys, 20, Relief::Divide(3)).unwrap();
+    let inspects: Vec<u64> = monkeys.iter().map(|m| m.inspects).collect();
+    assert_eq!(
+        exact_inspections(&parsed, 20, Relief::Divide(3)),
+        Ok(inspects.clone())
+    );
+    assert_eq!(
+        checked_inspections(&parsed, 20, Relief::Divide(3)),
+        Ok(inspects)
+    );
+
+    let exact = exact_inspections(&parsed, 20, Relief::None).unwrap();
+    assert_eq!(exact, [99, 97, 8, 103]);
+    assert_eq!(inspections_after(&parsed, mod_by, 20), Ok(exact));
+
+    match checked_inspections(&parsed, 20, Relief::None) {
+        Err(RoundError::Overflow { monkey, worry, .. }) => {
+            let worry: i64 = worry.parse().unwrap();
+            assert_eq!(monkey, 2);
+            assert!(worry.checked_mul(worry).is_none());
+        }
+        result => panic!("expected an overflow, got {:?}", result),
+    }
+}
+
+#[test]
+fn test_relief() {
+    const INPUT: &str = include_str!("../inputs/day11_test.txt");
+    let parsed = parse(INPUT).unwrap();
+    let inspects = |relief, rounds| {
+        let mut monkeys = parsed.clone();
+        play_rounds(&mut monkeys, rounds, relief).unwrap();
+        monkeys.iter().map(|m| m.inspects).collect::<Vec<_>>()
+    };
+
+    assert_eq!(Relief::lcm(&parsed).apply(96577 + 5), 5);
+    assert_eq!(inspects(Relief::Divide(3), 20), [101, 95, 7, 105]);
+    assert_eq!(inspects(Relief::lcm(&parsed), 20), [99, 97, 8, 103]);
+    assert_eq!(inspects(Relief::Custom(|w| w / 3), 20), [101, 95, 7, 105]);
+    assert_eq!(inspects(Relief::None, 1), [2, 4, 3, 6]);
+
+    assert_eq!(
+        exact_inspections(&parsed, 20, Relief::Custom(|w| w / 3)),
+        Ok(vec![101, 95, 7, 105])
+    );
+    assert_eq!(
+        exact_inspections(&parsed, 1, Relief::Divide(0)),
+        Err(RoundError::ZeroRelief)
+    );
+    assert_eq!(
+        play_round(&mut parsed.clone(), Relief::Modulo(0)),
+        Err(RoundError::ZeroRelief)
+    );
+}
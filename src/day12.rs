@@ -1,88 +1,141 @@
-use itertools::iproduct;
-
-#[derive(Clone)]
-struct Map {
-    map: Vec<Vec<i64>>,
-    width: usize,
-    height: usize,
-    start: Point,
-    goal: Point,
+use crate::error::ParseError;
+use crate::grid::Grid;
+use crate::point::{Dir, Point};
+use crate::solution::Solution;
+use std::collections::VecDeque;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Map {
+    pub grid: Grid<i64>,
+    pub start: Point,
+    pub goal: Point,
 }
 
-type Point = (usize, usize);
+pub struct Day12;
 
-fn main() {
-    const INPUT: &str = include_str!("../inputs/day12.txt");
-    let parsed = parse(INPUT);
-    let solution1 = solve1(&parsed);
-    let solution2 = solve2(&parsed);
+impl Solution for Day12 {
+    type Input = Map;
+    type Answer1 = i64;
+    type Answer2 = i64;
 
-    println!("Solution 1: {:}", solution1);
-    println!("Solution 2: {:}", solution2);
-}
+    fn parse(input: &str) -> Result<Map, ParseError> {
+        parse(input)
+    }
 
-fn solve1(m: &Map) -> i64 {
-    let dist = dists(m);
+    fn part1(input: &Map) -> i64 {
+        solve1(input)
+    }
 
-    dist[m.goal.0 + m.goal.1 * m.width]
+    fn part2(input: &Map) -> i64 {
+        solve2(input)
+    }
 }
 
-fn solve2(m: &Map) -> i64 {
-    let low_points =
-        iproduct!((0..m.width), (0..m.height)).filter(|(x, y)| m.map[*y][*x] == 'a' as i64);
+// The distances from the nearest of several starts, which start that was
+// and the point before on the way. Unreachable points are i64::MAX away and
+// have no origin.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Search {
+    pub dist: Grid<i64>,
+    pub origin: Grid<Option<Point>>,
+    pub prev: Grid<Option<Point>>,
+}
 
-    low_points
-        .map(|p| {
-            let mut new_map = (*m).clone();
-            new_map.start = p;
-            solve1(&new_map)
-        })
-        .min()
-        .unwrap()
+pub fn solve1(m: &Map) -> i64 {
+    dists(m)[m.goal]
 }
 
-fn dists(m: &Map) -> Vec<i64> {
-    // distance vector, implicitly: i64::MAX if not visited
-    let mut dist = vec![std::i64::MAX; m.width * m.height];
-    dist[m.start.0 + m.start.1 * m.width] = 0;
+pub fn solve2(m: &Map) -> i64 {
+    best_start(m).map_or(i64::MAX, |(_, dist)| dist)
+}
 
-    let mut cur;
-    let mut front = vec![m.start];
+// The lowest point closest to the goal, and how far it is
+pub fn best_start(m: &Map) -> Option<(Point, i64)> {
+    let low_points = m.grid.iter().filter(|(_, h)| **h == 'a' as i64);
+    let search = search(m, low_points.map(|(p, _)| p));
 
-    while !front.is_empty() {
-        cur = front.pop().unwrap(); // just tested to be unempty. probably a cleaner way to do this!
+    search.origin[m.goal].map(|start| (start, search.dist[m.goal]))
+}
+
+pub fn dists(m: &Map) -> Grid<i64> {
+    search(m, [m.start]).dist
+}
 
-        for (nx, ny) in neighbours(m, cur) {
-            let alt = 1 + dist[cur.0 + cur.1 * m.width];
-            if alt < dist[nx + ny * m.width] {
-                dist[nx + ny * m.width] = alt;
-                front.push((nx, ny))
+// A breadth first search from all the starts at once
+pub fn search<I: IntoIterator<Item = Point>>(m: &Map, starts: I) -> Search {
+    let mut dist = Grid::new(m.grid.width, m.grid.height, i64::MAX);
+    let mut origin = Grid::new(m.grid.width, m.grid.height, None);
+    let mut prev = Grid::new(m.grid.width, m.grid.height, None);
+    let mut front = VecDeque::new();
+    for start in starts {
+        dist[start] = 0;
+        origin[start] = Some(start);
+        front.push_back(start);
+    }
+
+    while let Some(cur) = front.pop_front() {
+        for n in neighbours(m, cur) {
+            if dist[n] == i64::MAX {
+                dist[n] = dist[cur] + 1;
+                origin[n] = origin[cur];
+                prev[n] = Some(cur);
+                front.push_back(n);
             }
         }
     }
 
-    dist
+    Search { dist, origin, prev }
 }
 
-fn parse(s: &str) -> Map {
-    let ls = s.lines();
-    let map: Vec<Vec<i64>> = ls.map(|l| l.chars().map(char_height).collect()).collect();
-    let width = map[0].len();
-    let height = map.len();
+impl Search {
+    // A shortest path from one of the starts to `p`, both included
+    pub fn path_to(&self, p: Point) -> Option<Vec<Point>> {
+        self.origin.get(p)?.as_ref()?;
+
+        let mut path: Vec<Point> = std::iter::successors(Some(p), |p| self.prev[*p]).collect();
+        path.reverse();
+        Some(path)
+    }
+}
 
-    let start = i_to_point(s.find('S').unwrap(), width);
-    let goal = i_to_point(s.find('E').unwrap(), width);
+// A shortest route from the start to the goal
+pub fn path(m: &Map) -> Option<Vec<Point>> {
+    search(m, [m.start]).path_to(m.goal)
+}
 
-    Map {
-        map,
-        width,
-        height,
-        start,
-        goal,
+// The map with the path drawn on it like in the puzzle: an arrow on every
+// step showing where it goes next, 'E' at the end and '.' everywhere else
+pub fn render_path(m: &Map, path: &[Point]) -> String {
+    let mut map = Grid::new(m.grid.width, m.grid.height, '.');
+    for step in path.windows(2) {
+        map[step[0]] = Dir::from_delta(step[1] - step[0]).map_or('?', |d| d.arrow());
+    }
+    if let Some(end) = path.last() {
+        map[*end] = 'E';
     }
+
+    map.render(|c| *c)
+}
+
+pub fn parse(s: &str) -> Result<Map, ParseError> {
+    let grid = Grid::parse(s, |c| match c {
+        'a'..='z' | 'S' | 'E' => Ok(char_height(c)),
+        _ => Err("unknown height"),
+    })?;
+
+    let find = |c: char| {
+        s.lines()
+            .enumerate()
+            .find_map(|(y, l)| l.find(c).map(|x| Point::new(x as i64, y as i64)))
+            .ok_or_else(|| ParseError::new(s, "", &format!("missing the '{:}' marker", c)))
+    };
+    let start = find('S')?;
+    let goal = find('E')?;
+
+    Ok(Map { grid, start, goal })
 }
 
-fn char_height(c: char) -> i64 {
+pub fn char_height(c: char) -> i64 {
     match c {
         'S' => 'a' as i64,
         'E' => 'z' as i64,
@@ -90,65 +143,50 @@ fn char_height(c: char) -> i64 {
     }
 }
 
-// get the map coordinate from index in string
-fn i_to_point(i: usize, width: usize) -> Point {
-    // i = x  + y * (width+1)
-    let x = i % (width + 1);
-    let y = i / (width + 1);
-    (x, y)
-}
-
 // The points around a point that are at most one higher
-fn neighbours(m: &Map, (x, y): Point) -> Vec<Point> {
-    let cur_height = m.map[y][x];
-    around(m, (x, y))
-        .iter()
-        .filter(|(a, b)| m.map[*b][*a] - cur_height <= 1)
-        .copied()
-        .collect()
-}
-
-fn around(m: &Map, (x, y): Point) -> Vec<Point> {
-    vec![(0, -1), (-1, 0), (1, 0), (0, 1)]
-        .iter()
-        .filter(|p| **p != (0, 0))
-        .filter(|(a, b)| {
-            (x as i64 + a >= 0)
-                && ((x as i64 + a) < m.width as i64)
-                && (y as i64 + b >= 0)
-                && ((m.height as i64) > (y as i64 + b))
-        })
-        .map(|(a, b)| ((x as i64 + a) as usize, (y as i64 + *b) as usize))
+pub fn neighbours(m: &Map, p: Point) -> Vec<Point> {
+    let cur_height = m.grid[p];
+    m.grid
+        .neighbours4(p)
+        .filter(|n| m.grid[*n] - cur_height <= 1)
         .collect()
 }
 
 #[test]
 fn test_parse() {
     const INPUT: &str = include_str!("../inputs/day12_test.txt");
-    let map = parse(INPUT);
-
-    assert_eq!(map.width, 8);
-    assert_eq!(map.height, 5);
-    assert_eq!(map.start, (0, 0));
-    assert_eq!(map.goal, (5, 2));
+    let map = parse(INPUT).unwrap();
+
+    assert_eq!(map.grid.width, 8);
+    assert_eq!(map.grid.height, 5);
+    assert_eq!(map.start, Point::new(0, 0));
+    assert_eq!(map.goal, Point::new(5, 2));
+
+    let err = parse("Sab\nc#E").unwrap_err();
+    assert_eq!((err.line, err.column, err.text.as_str()), (2, 2, "#"));
+    assert_eq!(
+        parse("Sab\ncdf").map_err(|e| e.to_string()),
+        Err(String::from("line 1, column 1: missing the 'E' marker"))
+    );
 }
 
 #[test]
-fn test_around() {
+fn test_neighbours() {
     const INPUT: &str = include_str!("../inputs/day12_test.txt");
-    let map = parse(INPUT);
+    let map = parse(INPUT).unwrap();
 
-    assert_eq!(around(&map, (0, 0)).len(), 2);
-    assert_eq!(around(&map, (1, 0)).len(), 3);
-    assert_eq!(around(&map, (1, 1)).len(), 4);
-    assert_eq!(around(&map, (4, 4)).len(), 3);
-    assert_eq!(around(&map, (7, 4)).len(), 2);
+    assert_eq!(map.grid.neighbours4(Point::new(0, 0)).count(), 2);
+    assert_eq!(map.grid.neighbours4(Point::new(1, 1)).count(), 4);
+    assert_eq!(map.grid.neighbours4(Point::new(7, 4)).count(), 2);
+
+    // 'c' at (2, 2) can't climb to the 's' at (3, 2)
+    assert_eq!(neighbours(&map, Point::new(2, 2)).len(), 3);
 }
 
 #[test]
 fn test_solve1() {
     const INPUT: &str = include_str!("../inputs/day12_test.txt");
-    let parsed = parse(INPUT);
+    let parsed = parse(INPUT).unwrap();
     let solution1 = solve1(&parsed);
     assert_eq!(solution1, 31);
 }
@@ -156,7 +194,55 @@ fn test_solve1() {
 #[test]
 fn test_solve2() {
     const INPUT: &str = include_str!("../inputs/day12_test.txt");
-    let parsed = parse(INPUT);
+    let parsed = parse(INPUT).unwrap();
     let solution1 = solve2(&parsed);
     assert_eq!(solution1, 29);
 }
+
+#[test]
+fn test_best_start() {
+    const INPUT: &str = include_str!("../inputs/day12_test.txt");
+    let parsed = parse(INPUT).unwrap();
+    assert_eq!(best_start(&parsed), Some((Point::new(0, 4), 29)));
+
+    // The same as searching from every low point on its own
+    let one_by_one = parsed
+        .grid
+        .iter()
+        .filter(|(_, h)| **h == 'a' as i64)
+        .map(|(p, _)| search(&parsed, [p]).dist[parsed.goal])
+        .min();
+    assert_eq!(one_by_one, Some(29));
+
+    let search = search(&parsed, [Point::new(0, 0), Point::new(0, 4)]);
+    assert_eq!(search.origin[Point::new(1, 0)], Some(Point::new(0, 0)));
+    assert_eq!(search.origin[parsed.goal], Some(Point::new(0, 4)));
+
+    let cut_off = parse("Sbz\nbcE").unwrap();
+    assert_eq!(best_start(&cut_off), None);
+    assert_eq!(solve1(&cut_off), i64::MAX);
+}
+
+#[test]
+fn test_path() {
+    const INPUT: &str = include_str!("../inputs/day12_test.txt");
+    let parsed = parse(INPUT).unwrap();
+    let route = path(&parsed).unwrap();
+
+    assert_eq!(route.len() as i64, solve1(&parsed) + 1);
+    assert_eq!(
+        (route[0], route[route.len() - 1]),
+        (parsed.start, parsed.goal)
+    );
+    for step in route.windows(2) {
+        assert!(neighbours(&parsed, step[0]).contains(&step[1]));
+    }
+    // Not the same route as in the puzzle, but just as short
+    assert_eq!(
+        render_path(&parsed, &route),
+        ">>vv<<<<\n..vvv<<^\n..vv>E^^\n..v>>>^^\n..>>>>>^"
+    );
+
+    let cut_off = parse("Sbz\nbcE").unwrap();
+    assert_eq!(path(&cut_off), None);
+}
@@ -1,3 +1,5 @@
+use crate::error::ParseError;
+use crate::solution::Solution;
 use nom::character::complete::digit1;
 use nom::combinator::map;
 use nom::{
@@ -8,7 +10,7 @@ use std::cmp::Ordering;
 use std::iter::zip;
 
 #[derive(Eq, Debug, PartialEq, Clone)]
-enum Signal {
+pub enum Signal {
     I(i64),
     L(Vec<Signal>),
 }
@@ -41,7 +43,7 @@ impl PartialOrd for Signal {
     }
 }
 
-fn parse_signal(input: &str) -> IResult<&str, Signal> {
+pub fn parse_signal(input: &str) -> IResult<&str, Signal> {
     alt((parse_l, parse_i))(input)
 }
 
@@ -58,23 +60,51 @@ fn parse_l(input: &str) -> IResult<&str, Signal> {
     map(delimited(tag("["), parse_inner, tag("]")), Signal::L)(input)
 }
 
-fn parse(input: &str) -> Vec<(Signal, Signal)> {
-    let input_pairs: Vec<Vec<&str>> = input.split("\n\n").map(|s| s.lines().collect()).collect();
-    input_pairs
-        .iter()
-        .map(|p| (parse_signal(p[0]).unwrap().1, parse_signal(p[1]).unwrap().1))
+// Parse a whole line as a packet
+fn parse_packet(line: &str) -> Result<Signal, ParseError> {
+    match parse_signal(line) {
+        Ok(("", signal)) => Ok(signal),
+        Ok((rest, _)) => Err(ParseError::new(
+            line,
+            rest,
+            "unexpected text after the packet",
+        )),
+        Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => {
+            Err(ParseError::new(line, e.input, "invalid packet at"))
+        }
+        Err(nom::Err::Incomplete(_)) => Err(ParseError::new(line, line, "incomplete packet")),
+    }
+}
+
+pub fn parse(input: &str) -> Result<Vec<(Signal, Signal)>, ParseError> {
+    input
+        .split("\n\n")
+        .enumerate()
+        .map(|(i, pair)| {
+            let lines: Vec<&str> = pair.lines().collect();
+            if lines.len() != 2 {
+                return Err(
+                    ParseError::new(pair, pair, "expected a pair of packets, found").offset(i * 3),
+                );
+            }
+
+            let a = parse_packet(lines[0]).map_err(|e| e.offset(i * 3))?;
+            let b = parse_packet(lines[1]).map_err(|e| e.offset(i * 3 + 1))?;
+            Ok((a, b))
+        })
         .collect()
 }
 
-fn parse2(input: &str) -> Vec<Signal> {
+pub fn parse2(input: &str) -> Result<Vec<Signal>, ParseError> {
     input
         .lines()
-        .filter_map(|l| parse_signal(l).ok())
-        .map(|r| r.1)
+        .enumerate()
+        .filter(|(_, l)| !l.is_empty())
+        .map(|(i, l)| parse_packet(l).map_err(|e| e.offset(i)))
         .collect()
 }
 
-fn solve1(input: &Vec<(Signal, Signal)>) -> i64 {
+pub fn solve1(input: &[(Signal, Signal)]) -> i64 {
     input
         .iter()
         .enumerate()
@@ -83,7 +113,7 @@ fn solve1(input: &Vec<(Signal, Signal)>) -> i64 {
         .sum()
 }
 
-fn solve2(input: &mut Vec<Signal>) -> i64 {
+pub fn solve2(input: &mut Vec<Signal>) -> i64 {
     let mark1 = parse_signal("[[2]]").unwrap().1;
     let mark2 = parse_signal("[[6]]").unwrap().1;
     input.push(mark1.clone());
@@ -96,15 +126,28 @@ fn solve2(input: &mut Vec<Signal>) -> i64 {
     i1 * i2
 }
 
-fn main() {
-    const INPUT: &str = include_str!("../inputs/day13.txt");
-    let parsed = parse(INPUT);
-    let solution1 = solve1(&parsed);
-    let mut parsed2 = parse2(INPUT);
-    let solution2 = solve2(&mut parsed2);
+pub struct Day13;
+
+impl Solution for Day13 {
+    type Input = Vec<(Signal, Signal)>;
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> i64 {
+        solve1(input)
+    }
 
-    println!("Solution 1: {:}", solution1);
-    println!("Solution 2: {:}", solution2);
+    fn part2(input: &Self::Input) -> i64 {
+        let mut signals = input
+            .iter()
+            .flat_map(|(a, b)| [a.clone(), b.clone()])
+            .collect();
+        solve2(&mut signals)
+    }
 }
 
 #[test]
@@ -126,6 +169,14 @@ fn test_parse_signal() {
     )
 }
 
+#[test]
+fn test_parse_errors() {
+    let err = parse("[1,2]\n[3]\n\n[1,[2]\n[4]").unwrap_err();
+    assert_eq!(err.line, 4);
+    assert!(parse("[1]\n[2]x").is_err());
+    assert_eq!(parse2("[1]\n\n[a]").map_err(|e| e.line), Err(3));
+}
+
 #[test]
 fn test_signal_ord() {
     assert!(Signal::I(1) < Signal::I(2));
@@ -150,7 +201,7 @@ fn test_signal_ord() {
 #[test]
 fn test_solve1() {
     const INPUT: &str = include_str!("../inputs/day13_test.txt");
-    let parsed = parse(INPUT);
+    let parsed = parse(INPUT).unwrap();
     let solution1 = solve1(&parsed);
     assert_eq!(solution1, 13);
 }
@@ -158,7 +209,7 @@ fn test_solve1() {
 #[test]
 fn test_solve2() {
     const INPUT: &str = include_str!("../inputs/day13_test.txt");
-    let mut parsed = parse2(INPUT);
+    let mut parsed = parse2(INPUT).unwrap();
     let solution = solve2(&mut parsed);
     assert_eq!(solution, 140);
 }
@@ -1,81 +1,126 @@
-use std::collections::HashMap;
+use crate::error::{parse_lines, parse_num, ParseError};
+use crate::grid::Grid;
+use crate::point::Point;
+use crate::solution::Solution;
 
+// The scan, with x shifted by `left` so that it starts at 0 on the grid
 #[derive(Clone)]
-struct Map {
-    height: i64,
-    m: HashMap<Point, char>,
+pub struct Map {
+    pub height: i64,
+    pub left: i64,
+    pub grid: Grid<char>,
 }
 
-type Point = (i64, i64);
+const SOURCE: Point = Point::new(500, 0);
 
-fn main() {
-    const INPUT: &str = include_str!("../inputs/day14.txt");
-    let parsed = parse(INPUT);
-    let mut m = build_map(parsed);
-    let solution1 = solve1(&mut m.clone());
+pub struct Day14;
+
+impl Solution for Day14 {
+    type Input = Map;
+    type Answer1 = usize;
+    type Answer2 = usize;
 
-    let solution2 = solve2(&mut m);
+    fn parse(input: &str) -> Result<Map, ParseError> {
+        parse(input).map(build_map)
+    }
 
-    println!("Solution 1: {:}", solution1);
-    println!("Solution 2: {:}", solution2);
+    fn part1(input: &Map) -> usize {
+        solve1(&mut input.clone())
+    }
+
+    fn part2(input: &Map) -> usize {
+        solve2(&mut input.clone())
+    }
 }
 
-fn segment_points((a1, a2): Point, (b1, b2): Point) -> Vec<Point> {
-    if a1 == b1 {
-        if a2 < b2 {
-            (a2..b2 + 1).map(|snd| (a1, snd)).collect()
-        } else {
-            (b2..a2 + 1).map(|snd| (a1, snd)).collect()
-        }
-    } else {
-        if a1 < b1 {
-            (a1..b1 + 1).map(|fst| (fst, a2)).collect()
-        } else {
-            (b1..a1 + 1).map(|fst| (fst, a2)).collect()
-        }
+fn segment_points(a: Point, b: Point) -> Vec<Point> {
+    let mut points = vec![a];
+    let mut p = a;
+    while p != b {
+        p = p.step_towards(b);
+        points.push(p);
     }
+    points
 }
 
-fn wall_points(ps: &Vec<Point>) -> Vec<Point> {
+fn wall_points(ps: &[Point]) -> Vec<Point> {
     ps.windows(2)
         .flat_map(|w| segment_points(w[0], w[1]))
         .collect()
 }
 
-fn parse_line(s: &str) -> Vec<Point> {
-    s.split(" -> ").map(parse_point).collect()
-}
-
-fn parse_point(s: &str) -> Point {
-    let parts: Vec<&str> = s.split(',').collect();
-    (parts[0].parse().unwrap(), parts[1].parse().unwrap())
-}
+fn parse_line(s: &str) -> Result<Vec<Point>, ParseError> {
+    s.split(" -> ").map(|p| parse_point(s, p)).collect()
+}
+
+fn parse_point(line: &str, s: &str) -> Result<Point, ParseError> {
+    let (x, y) = s
+        .split_once(',')
+        .ok_or_else(|| ParseError::new(line, s, "expected a point, found"))?;
+    Ok(Point::new(parse_num(line, x)?, parse_num(line, y)?))
+}
+
+pub fn parse(s: &str) -> Result<Vec<Vec<Point>>, ParseError> {
+    parse_lines(s, parse_line)
+}
+
+pub fn build_map(walls: Vec<Vec<Point>>) -> Map {
+    let points: Vec<Point> = walls.iter().flat_map(|w| wall_points(w)).collect();
+    // walls above the source don't make the cave any shallower
+    let height = points.iter().map(|p| p.y).max().unwrap_or(0).max(0);
+
+    // room for the floor two rows below the lowest wall, and for the sand
+    // piling up on it on both sides of the source
+    let left = points
+        .iter()
+        .map(|p| p.x)
+        .min()
+        .unwrap_or(SOURCE.x)
+        .min(SOURCE.x - height - 3);
+    let right = points
+        .iter()
+        .map(|p| p.x)
+        .max()
+        .unwrap_or(SOURCE.x)
+        .max(SOURCE.x + height + 3);
+    let mut map = Map {
+        height,
+        left,
+        grid: Grid::new((right - left + 1) as usize, height as usize + 3, '.'),
+    };
+    for p in points {
+        // walls above the source can't stop any sand
+        if let Some(c) = map.grid.get_mut(map.at(p)) {
+            *c = '#';
+        }
+    }
 
-fn parse(s: &str) -> Vec<Vec<Point>> {
-    s.lines().map(parse_line).collect()
+    map
 }
 
-fn build_map(walls: Vec<Vec<Point>>) -> Map {
-    let points: Vec<Point> = walls.iter().flat_map(wall_points).collect();
-    let height = points.iter().map(|p| p.1).max().unwrap_or(0);
-    let m: HashMap<Point, char> = points.iter().map(|p| (*p, '#')).collect();
-
-    Map { height, m }
+impl Map {
+    // Where a point of the scan is on the grid
+    pub fn at(&self, p: Point) -> Point {
+        p - Point::new(self.left, 0)
+    }
 }
 
-fn drop_sand_1(map: &Map, (x, y): Point) -> Option<Point> {
-    let candidates = [(x, y + 1), (x - 1, y + 1), (x + 1, y + 1)];
-    candidates.into_iter().find(|c| !map.m.contains_key(c))
+fn drop_sand_1(map: &Map, p: Point) -> Option<Point> {
+    [Point::new(0, 1), Point::new(-1, 1), Point::new(1, 1)]
+        .into_iter()
+        .filter_map(|d| map.grid.offset(map.at(p), d))
+        .find(|c| map.grid[*c] == '.')
+        .map(|c| c + Point::new(map.left, 0))
 }
 
 // Spawn a unit of sand on the map and drop it as far as it will go
-fn drop_sand(map: &Map) -> Option<Point> {
-    let mut new_sand = (500, 0);
-    if map.m.contains_key(&new_sand) {
+pub fn drop_sand(map: &Map) -> Option<Point> {
+    let mut new_sand = SOURCE;
+    if map.grid[map.at(new_sand)] != '.' {
         return None;
     };
 
-    while new_sand.1 <= map.height {
+    while new_sand.y <= map.height {
         match drop_sand_1(map, new_sand) {
             Some(next_sand) => new_sand = next_sand,
             None => return Some(new_sand),
@@ -84,40 +129,50 @@ fn drop_sand(map: &Map) -> Option<Point> {
     None
 }
 
-fn play(map: &mut Map) {
+pub fn play(map: &mut Map) {
     while let Some(next_sand) = drop_sand(map) {
-        map.m.insert(next_sand, 'o');
+        let p = map.at(next_sand);
+        map.grid[p] = 'o';
     }
 }
 
-fn solve1(map: &mut Map) -> usize {
+fn count_sand(map: &Map) -> usize {
+    map.grid.iter().filter(|(_, c)| **c == 'o').count()
+}
+
+pub fn solve1(map: &mut Map) -> usize {
     play(map);
-    map.m.iter().filter(|(_, v)| **v == 'o').count()
+    count_sand(map)
 }
 
-fn solve2(map: &mut Map) -> usize {
+pub fn solve2(map: &mut Map) -> usize {
     map.height += 2;
-    (0..1000).for_each(|x| {
-        map.m.insert((x, map.height), '#');
-    });
+    for x in 0..map.grid.width {
+        map.grid[Point::new(x as i64, map.height)] = '#';
+    }
 
     play(map);
-    map.m.iter().filter(|(_, v)| **v == 'o').count()
+    count_sand(map)
 }
 
 #[test]
 fn test_parse() {
-    assert_eq!(parse_point("498,4"), (498, 4));
+    let p = Point::new;
+    assert_eq!(parse_point("498,4", "498,4"), Ok(p(498, 4)));
     assert_eq!(
         parse_line("498,4 -> 498,6 -> 496,6"),
-        vec![(498, 4), (498, 6), (496, 6)]
+        Ok(vec![p(498, 4), p(498, 6), p(496, 6)])
+    );
+    assert_eq!(
+        parse_line("498,4 -> 498;6").map_err(|e| (e.column, e.text)),
+        Err((10, String::from("498;6")))
     );
 }
 
 #[test]
 fn test_solve1() {
     const INPUT: &str = include_str!("../inputs/day14_test.txt");
-    let parsed = parse(INPUT);
+    let parsed = parse(INPUT).unwrap();
     let mut m = build_map(parsed);
     let solution = solve1(&mut m);
     assert_eq!(solution, 24);
@@ -126,8 +181,36 @@ fn test_solve1() {
 #[test]
 fn test_solve2() {
     const INPUT: &str = include_str!("../inputs/day14_test.txt");
-    let parsed = parse(INPUT);
+    let parsed = parse(INPUT).unwrap();
     let mut m = build_map(parsed);
     let solution = solve2(&mut m);
     assert_eq!(solution, 93);
 }
+
+#[test]
+fn test_map_edges() {
+    // a wall left of x = 0
+    let mut m = build_map(parse("-1,4 -> 2,4").unwrap());
+    assert_eq!(m.grid[m.at(Point::new(-1, 4))], '#');
+    assert_eq!(solve1(&mut m), 0);
+
+    // walls that are all above the source
+    let mut m = build_map(parse("500,-5 -> 501,-5").unwrap());
+    assert_eq!(solve1(&mut m), 0);
+    let mut m = build_map(parse("500,-5 -> 501,-5").unwrap());
+    assert_eq!(solve2(&mut m), 2 * 2);
+
+    // the pile in part 2 is a triangle as wide as the floor is deep, minus
+    // the two wall cells; the sand fills the space under the wall from the
+    // sides
+    let mut m = build_map(parse("500,50 -> 501,50").unwrap());
+    let floor = 52;
+    assert_eq!(solve2(&mut m), floor * floor - 2);
+
+    // a scan so deep that the pile spreads past x = 0
+    let m = build_map(parse("500,600 -> 501,600").unwrap());
+    let floor = 602;
+    for x in [SOURCE.x - floor, SOURCE.x + floor] {
+        assert!(m.grid.contains(m.at(Point::new(x, floor))));
+    }
+}
@@ -1,57 +1,70 @@
+use crate::error::{parse_lines, parse_num, ParseError};
+use crate::point::Point;
+use crate::solution::Solution;
 use std::collections::HashSet;
 
-type Point = (i64, i64);
+pub struct Day15;
 
-fn main() {
-    const INPUT: &str = include_str!("../inputs/day15.txt");
-    let parsed = parse(INPUT);
+impl Solution for Day15 {
+    type Input = Vec<(Point, Point)>;
+    type Answer1 = i64;
+    type Answer2 = i64;
 
-    let solution1 = solve1(2000000, parsed.clone());
-    println!("Solution 1: {:}", solution1);
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
 
-    let solution2 = solve2(4000000, parsed);
-    println!("Solution 2: {:}", solution2);
-}
+    fn part1(input: &Self::Input) -> i64 {
+        solve1(2000000, input.clone())
+    }
 
-fn parse_point(s: &str) -> Point {
-    let parts: Vec<&str> = s.split(", ").collect();
-    (
-        parts[0].split('=').nth(1).unwrap().parse().unwrap(),
-        parts[1].split('=').nth(1).unwrap().parse().unwrap(),
-    )
+    fn part2(input: &Self::Input) -> i64 {
+        solve2(4000000, input.clone())
+    }
 }
 
-fn parse_line(s: &str) -> (Point, Point) {
-    let parts: Vec<&str> = s.split(": closest beacon is at ").collect();
-    (parse_point(&parts[0][10..]), parse_point(parts[1]))
+// "x=1, y=2"
+fn parse_point(line: &str, s: &str) -> Result<Point, ParseError> {
+    let coords = s
+        .strip_prefix("x=")
+        .and_then(|s| s.split_once(", y="))
+        .ok_or_else(|| ParseError::new(line, s, "expected a position, found"))?;
+    Ok(Point::new(
+        parse_num(line, coords.0)?,
+        parse_num(line, coords.1)?,
+    ))
 }
 
-fn parse(s: &str) -> Vec<(Point, Point)> {
-    s.lines().map(parse_line).collect()
+fn parse_line(s: &str) -> Result<(Point, Point), ParseError> {
+    let parts = s
+        .strip_prefix("Sensor at ")
+        .and_then(|s| s.split_once(": closest beacon is at "))
+        .ok_or_else(|| ParseError::new(s, s, "expected a sensor and a beacon, found"))?;
+    Ok((parse_point(s, parts.0)?, parse_point(s, parts.1)?))
 }
 
-fn manhattan((a1, a2): Point, (b1, b2): Point) -> i64 {
-    (a1 - b1).abs() + (a2 - b2).abs()
+pub fn parse(s: &str) -> Result<Vec<(Point, Point)>, ParseError> {
+    parse_lines(s, parse_line)
 }
 
 // return the endpoints of an excluded area on the line y
-fn line_exclusions(y: i64, scanner: Point, beacon: Point) -> (i64, i64) {
-    let dist = manhattan(scanner, beacon);
-    let excl_width = dist - (y - scanner.1).abs();
+pub fn line_exclusions(y: i64, scanner: Point, beacon: Point) -> (i64, i64) {
+    let dist = scanner.manhattan(beacon);
+    let excl_width = dist - (y - scanner.y).abs();
 
-    (scanner.0 - excl_width, scanner.0 + excl_width)
+    (scanner.x - excl_width, scanner.x + excl_width)
 }
 
-fn solve1(y: i64, pairs: Vec<(Point, Point)>) -> i64 {
+pub fn solve1(y: i64, pairs: Vec<(Point, Point)>) -> i64 {
     let mut exclusions: HashSet<i64> = pairs
         .iter()
         .map(|(s, b)| line_exclusions(y, *s, *b))
         .flat_map(|(start, end)| start..end + 1)
         .collect();
 
-    let beacons_on_line = pairs.iter().map(|(_, b)| b).filter(|(_, by)| *by == y);
-    beacons_on_line.for_each(|(bx, _)| {
-        let _ = exclusions.remove(bx);
+    let beacons_on_line = pairs.iter().map(|(_, b)| b).filter(|b| b.y == y);
+    beacons_on_line.for_each(|b| {
+        let _ = exclusions.remove(&b.x);
     });
 
     let mut vals: Vec<&i64> = exclusions.iter().collect();
@@ -63,22 +76,22 @@ fn solve1(y: i64, pairs: Vec<(Point, Point)>) -> i64 {
 // Since we know that there can only be one point not covered by the
 // scanners, it needs to be on a point right outside the range of some
 // scanner and it's enough to check those.
-fn solve2(search_size: usize, pairs: Vec<(Point, Point)>) -> i64 {
-    let coverages: Vec<(Point, i64)> = pairs.iter().map(|(s, b)| (*s, manhattan(*s, *b))).collect();
+pub fn solve2(search_size: usize, pairs: Vec<(Point, Point)>) -> i64 {
+    let coverages: Vec<(Point, i64)> = pairs.iter().map(|(s, b)| (*s, s.manhattan(*b))).collect();
 
     let mut points = coverages
         .iter()
         .flat_map(|(p, c)| around(search_size as i64, *p, *c));
 
-    let found = points.find(|(x, y)| !is_covered((*x as i64, *y as i64), &coverages));
+    let found = points.find(|p| !is_covered(*p, &coverages));
 
     let uw = found.unwrap();
-    (uw.0 as i64) * 4000000 + uw.1 as i64
+    uw.x * 4000000 + uw.y
 }
 
-fn is_covered(p: Point, coverages: &Vec<(Point, i64)>) -> bool {
+fn is_covered(p: Point, coverages: &[(Point, i64)]) -> bool {
     for (s, r) in coverages {
-        if manhattan(p, *s) <= *r {
+        if p.manhattan(*s) <= *r {
             return true;
         }
     }
@@ -86,17 +99,18 @@ fn is_covered(p: Point, coverages: &Vec<(Point, i64)>) -> bool {
 }
 
 // Return the points around a scanner's scan radius
-fn around(search_size: i64, (x, y): Point, r: i64) -> Vec<Point> {
+fn around(search_size: i64, p: Point, r: i64) -> Vec<Point> {
     (0..r + 1)
         .flat_map(|i| {
             [
-                (x + -r - 1 + i, y + i),
-                (x + i, y + -r - 1 + i),
-                (x + r + 1 - i, y + i),
-                (x + -i, y + r + 1 - i),
+                Point::new(-r - 1 + i, i),
+                Point::new(i, -r - 1 + i),
+                Point::new(r + 1 - i, i),
+                Point::new(-i, r + 1 - i),
             ]
         })
-        .filter(|(a, b)| *a >= 0 && *a <= search_size && *b >= 0 && *b <= search_size)
+        .map(|d| p + d)
+        .filter(|a| a.x >= 0 && a.x <= search_size && a.y >= 0 && a.y <= search_size)
         .collect()
 }
 
@@ -104,20 +118,26 @@ fn around(search_size: i64, (x, y): Point, r: i64) -> Vec<Point> {
 fn test_parse_line() {
     assert_eq!(
         parse_line("Sensor at x=2, y=18: closest beacon is at x=-2, y=15"),
-        ((2, 18), (-2, 15))
+        Ok((Point::new(2, 18), Point::new(-2, 15)))
+    );
+    assert_eq!(
+        parse_line("Sensor at x=2, y=18: closest beacon is at x=-2, y=1a")
+            .map_err(|e| (e.column, e.text)),
+        Err((51, String::from("1a")))
     );
+    assert!(parse_line("Sensor at x=2, y=18").is_err());
 }
 
 #[test]
 fn test_line_exclusion() {
-    let res = line_exclusions(10, (8, 7), (2, 10));
+    let res = line_exclusions(10, Point::new(8, 7), Point::new(2, 10));
     assert_eq!(res, (2, 14));
 }
 
 #[test]
 fn test_solve1() {
     const INPUT: &str = include_str!("../inputs/day15_test.txt");
-    let parsed = parse(INPUT);
+    let parsed = parse(INPUT).unwrap();
 
     let solution = solve1(10, parsed);
     assert_eq!(solution, 26);
@@ -125,13 +145,13 @@ fn test_solve1() {
 
 #[test]
 fn test_around() {
-    assert_eq!(around(100, (10, 10), 5).len(), 4 * 6);
+    assert_eq!(around(100, Point::new(10, 10), 5).len(), 4 * 6);
 }
 
 #[test]
 fn test_solve2() {
     const INPUT: &str = include_str!("../inputs/day15_test.txt");
-    let parsed = parse(INPUT);
+    let parsed = parse(INPUT).unwrap();
 
     let solution = solve2(20, parsed);
     assert_eq!(solution, 56000011);
@@ -1 +1,568 @@
-fn main() {}
+use crate::error::{parse_lines, parse_num, ParseError};
+use crate::solution::Solution;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Valve {
+    pub name: String,
+    pub rate: i64,
+    pub tunnels: Vec<String>,
+}
+
+// The valves worth opening, and the start valve as the last one, with the
+// minutes it takes to walk between each of them
+#[derive(Clone, Debug, PartialEq)]
+pub struct Network {
+    pub names: Vec<String>,
+    pub rates: Vec<i64>,
+    pub dist: Vec<Vec<i64>>,
+}
+
+// One valve being opened: the valves walked through to get there, the
+// minute it's opened in and the pressure it releases by the end
+#[derive(Clone, Debug, PartialEq)]
+pub struct Opening {
+    pub valve: String,
+    pub minute: i64,
+    pub path: Vec<String>,
+    pub released: i64,
+}
+
+// The valves each agent opens, in order
+#[derive(Clone, Debug, PartialEq)]
+pub struct Plan {
+    pub start: String,
+    pub time: i64,
+    pub routes: Vec<Vec<Opening>>,
+}
+
+pub const START: &str = "AA";
+
+pub struct Day16;
+
+impl Solution for Day16 {
+    type Input = Vec<Valve>;
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    fn parse(input: &str) -> Result<Vec<Valve>, ParseError> {
+        let valves = parse(input)?;
+        if !valves.iter().any(|v| v.name == START) {
+            let message = format!("missing the start valve {:}", START);
+            return Err(ParseError::new(input, "", &message));
+        }
+        Ok(valves)
+    }
+
+    fn part1(input: &Vec<Valve>) -> i64 {
+        solve1(input)
+    }
+
+    fn part2(input: &Vec<Valve>) -> i64 {
+        solve2(input)
+    }
+}
+
+// "Valve BB has flow rate=13; tunnels lead to valves CC, AA"
+fn parse_line(s: &str) -> Result<Valve, ParseError> {
+    let (name, rest) = s
+        .strip_prefix("Valve ")
+        .and_then(|s| s.split_once(" has flow rate="))
+        .ok_or_else(|| ParseError::new(s, s, "expected a valve, found"))?;
+    let (rate, tunnels) = rest
+        .split_once("; ")
+        .ok_or_else(|| ParseError::new(s, rest, "expected a flow rate and tunnels, found"))?;
+    let tunnels = tunnels
+        .strip_prefix("tunnels lead to valves ")
+        .or_else(|| tunnels.strip_prefix("tunnel leads to valve "))
+        .ok_or_else(|| ParseError::new(s, tunnels, "expected tunnels, found"))?;
+
+    Ok(Valve {
+        name: String::from(name),
+        rate: parse_num(s, rate)?,
+        tunnels: tunnels.split(", ").map(String::from).collect(),
+    })
+}
+
+pub fn parse(s: &str) -> Result<Vec<Valve>, ParseError> {
+    let valves = parse_lines(s, parse_line)?;
+
+    // every tunnel has to lead somewhere
+    for (i, (valve, line)) in valves.iter().zip(s.lines()).enumerate() {
+        if let Some(t) = valve
+            .tunnels
+            .iter()
+            .find(|t| !valves.iter().any(|v| v.name == **t))
+        {
+            let at = line.rfind(t.as_str()).unwrap_or(0);
+            let text = &line[at..at + t.len()];
+            return Err(ParseError::new(line, text, "unknown valve").offset(i));
+        }
+    }
+
+    Ok(valves)
+}
+
+impl Network {
+    // None if there's no valve called `start`
+    pub fn new(valves: &[Valve], start: &str) -> Option<Network> {
+        let index: HashMap<&str, usize> = valves
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (v.name.as_str(), i))
+            .collect();
+        let start = *index.get(start)?;
+
+        // Floyd-Warshall over all the valves
+        let n = valves.len();
+        let mut all = vec![vec![i64::MAX / 2; n]; n];
+        for (i, v) in valves.iter().enumerate() {
+            all[i][i] = 0;
+            for t in v.tunnels.iter() {
+                all[i][index[t.as_str()]] = 1;
+            }
+        }
+        for k in 0..n {
+            for i in 0..n {
+                for j in 0..n {
+                    all[i][j] = all[i][j].min(all[i][k] + all[k][j]);
+                }
+            }
+        }
+
+        let mut kept: Vec<usize> = (0..n).filter(|i| valves[*i].rate > 0).collect();
+        kept.push(start);
+
+        Some(Network {
+            names: kept.iter().map(|i| valves[*i].name.clone()).collect(),
+            rates: kept.iter().map(|i| valves[*i].rate).collect(),
+            dist: kept
+                .iter()
+                .map(|i| kept.iter().map(|j| all[*i][*j]).collect())
+                .collect(),
+        })
+    }
+
+    pub fn start(&self) -> usize {
+        self.names.len() - 1
+    }
+
+    // The number of valves worth opening
+    pub fn useful(&self) -> usize {
+        self.names.len() - 1
+    }
+}
+
+// The most pressure one agent can release in `time` minutes by opening
+// exactly the valves in each set, indexed by a bit mask of the valves
+pub fn best_per_set(net: &Network, time: i64) -> Vec<i64> {
+    let mut best = vec![0; 1 << net.useful()];
+    visit(net, net.start(), time, 0, 0, &mut best);
+    best
+}
+
+fn visit(net: &Network, at: usize, time: i64, opened: usize, pressure: i64, best: &mut [i64]) {
+    best[opened] = best[opened].max(pressure);
+
+    for next in 0..net.useful() {
+        if opened & (1 << next) != 0 {
+            continue;
+        }
+        // walk there and spend a minute opening it
+        let left = time - net.dist[at][next] - 1;
+        if left <= 0 {
+            continue;
+        }
+        let released = pressure + left * net.rates[next];
+        visit(net, next, left, opened | (1 << next), released, best);
+    }
+}
+
+pub fn max_pressure(net: &Network, time: i64) -> i64 {
+    best_per_set(net, time).into_iter().max().unwrap_or(0)
+}
+
+// The best of each set and any of its subsets, and which subset that is
+fn best_within(net: &Network, best: &[i64]) -> Vec<(i64, usize)> {
+    let mut within: Vec<(i64, usize)> = best.iter().copied().zip(0..).collect();
+    for mask in 0..best.len() {
+        for i in 0..net.useful() {
+            if mask & (1 << i) != 0 {
+                within[mask] = within[mask].max(within[mask ^ (1 << i)]);
+            }
+        }
+    }
+    within
+}
+
+// All the subsets of a set, the set itself first
+fn subsets(set: usize) -> impl Iterator<Item = usize> {
+    std::iter::successors(Some(set), move |sub| match sub {
+        0 => None,
+        _ => Some((sub - 1) & set),
+    })
+}
+
+// Agents working in parallel open disjoint sets of valves. Returns the
+// pressure released and the set each of them opens.
+pub fn best_split(net: &Network, time: i64, agents: usize) -> (i64, Vec<usize>) {
+    if agents == 0 {
+        return (0, vec![]);
+    }
+    let best = best_per_set(net, time);
+    let all = best.len() - 1;
+
+    // layers[k][mask]: the most k + 1 agents can release with the valves in
+    // mask, and the set the first of them opens. Only the whole set matters
+    // for the last layer.
+    let mut layers = vec![best_within(net, &best)];
+    for k in 1..agents {
+        let masks = if k == agents - 1 { all..=all } else { 0..=all };
+        let mut layer = vec![(0, 0); all + 1];
+        for mask in masks {
+            layer[mask] = subsets(mask)
+                .map(|sub| (best[sub] + layers[k - 1][mask ^ sub].0, sub))
+                .max()
+                .unwrap_or((0, 0));
+        }
+        layers.push(layer);
+    }
+
+    let pressure = layers[agents - 1][all].0;
+    let mut left = all;
+    let sets = layers
+        .iter()
+        .rev()
+        .map(|layer| {
+            let set = layer[left].1;
+            left ^= set;
+            set
+        })
+        .collect();
+
+    (pressure, sets)
+}
+
+pub fn max_pressure_pair(net: &Network, time: i64) -> i64 {
+    best_split(net, time, 2).0
+}
+
+// The order to open the valves of a set in to release the most pressure
+pub fn best_route(net: &Network, time: i64, set: usize) -> Vec<usize> {
+    fn go(net: &Network, at: usize, time: i64, left: usize) -> (i64, Vec<usize>) {
+        let mut best = (0, vec![]);
+        for next in (0..net.useful()).filter(|i| left & (1 << i) != 0) {
+            let t = time - net.dist[at][next] - 1;
+            if t <= 0 {
+                continue;
+            }
+            let (pressure, mut route) = go(net, next, t, left ^ (1 << next));
+            if pressure + t * net.rates[next] > best.0 {
+                route.insert(0, next);
+                best = (pressure + t * net.rates[next], route);
+            }
+        }
+        best
+    }
+
+    go(net, net.start(), time, set).1
+}
+
+// The tunnels to walk from one valve to another, both included
+pub fn shortest_path(valves: &[Valve], from: &str, to: &str) -> Vec<String> {
+    let mut previous: HashMap<&str, &str> = HashMap::new();
+    let mut front = VecDeque::from([from]);
+
+    while let Some(cur) = front.pop_front() {
+        if cur == to {
+            break;
+        }
+        let valve = valves.iter().find(|v| v.name == cur);
+        for t in valve.iter().flat_map(|v| v.tunnels.iter()) {
+            if t != from && !previous.contains_key(t.as_str()) {
+                previous.insert(t, cur);
+                front.push_back(t);
+            }
+        }
+    }
+
+    let mut path = vec![String::from(to)];
+    let mut cur = to;
+    while let Some(prev) = previous.get(cur) {
+        path.insert(0, String::from(*prev));
+        cur = prev;
+    }
+    path
+}
+
+impl Plan {
+    // Walk the routes through the network, one per agent
+    pub fn new(valves: &[Valve], net: &Network, time: i64, routes: &[Vec<usize>]) -> Plan {
+        let routes = routes
+            .iter()
+            .map(|route| {
+                let mut at = net.start();
+                let mut left = time;
+                route
+                    .iter()
+                    .map(|next| {
+                        left -= net.dist[at][*next] + 1;
+                        let path = shortest_path(valves, &net.names[at], &net.names[*next]);
+                        at = *next;
+                        Opening {
+                            valve: net.names[at].clone(),
+                            minute: time - left,
+                            path,
+                            released: left * net.rates[at],
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Plan {
+            start: net.names[net.start()].clone(),
+            time,
+            routes,
+        }
+    }
+
+    pub fn released(&self) -> i64 {
+        self.routes.iter().flatten().map(|o| o.released).sum()
+    }
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, route) in self.routes.iter().enumerate() {
+            writeln!(f, "Agent {:}:", i + 1)?;
+            for o in route {
+                writeln!(
+                    f,
+                    "  minute {:>2}: open {:} ({:}), releasing {:}",
+                    o.minute,
+                    o.valve,
+                    o.path.join(" -> "),
+                    o.released
+                )?;
+            }
+        }
+        write!(
+            f,
+            "Released in {:} minutes: {:}",
+            self.time,
+            self.released()
+        )
+    }
+}
+
+// The best plan for any number of agents starting from any valve, or None
+// if there's no such valve
+pub fn plan(valves: &[Valve], start: &str, time: i64, agents: usize) -> Option<Plan> {
+    let net = Network::new(valves, start)?;
+    let (_, sets) = best_split(&net, time, agents);
+    let routes: Vec<Vec<usize>> = sets
+        .iter()
+        .map(|set| best_route(&net, time, *set))
+        .collect();
+
+    Some(Plan::new(valves, &net, time, &routes))
+}
+
+pub fn solo_plan(valves: &[Valve], time: i64) -> Plan {
+    plan(valves, START, time, 1).unwrap()
+}
+
+pub fn pair_plan(valves: &[Valve], time: i64) -> Plan {
+    plan(valves, START, time, 2).unwrap()
+}
+
+// Colours for the routes of each agent in a DOT graph
+const ROUTE_COLOURS: [&str; 4] = ["red", "blue", "darkgreen", "orange"];
+
+// The tunnel network as an undirected Graphviz graph. Valves with no flow are
+// greyed out, and the valves and tunnels in a plan are coloured by agent.
+pub fn to_dot(valves: &[Valve], plan: Option<&Plan>) -> String {
+    let mut opened: HashMap<&str, (&str, i64)> = HashMap::new();
+    let mut walked: HashMap<(&str, &str), &str> = HashMap::new();
+    for (route, colour) in plan
+        .iter()
+        .flat_map(|p| p.routes.iter())
+        .zip(ROUTE_COLOURS.iter().cycle())
+    {
+        for o in route {
+            opened.insert(&o.valve, (colour, o.minute));
+            for w in o.path.windows(2) {
+                let edge = (w[0].as_str().min(&w[1]), w[0].as_str().max(&w[1]));
+                walked.entry(edge).or_insert(colour);
+            }
+        }
+    }
+
+    let mut dot = String::from("graph valves {\n");
+    for v in valves {
+        let mut attrs = vec![];
+        match (v.rate, opened.get(v.name.as_str())) {
+            (_, Some((colour, minute))) => {
+                attrs.push(format!(
+                    "label=\"{:}\\n{:}\\nminute {:}\"",
+                    v.name, v.rate, minute
+                ));
+                attrs.push(format!("style=filled, fillcolor={:}", colour));
+            }
+            (0, None) => attrs.push(format!("label=\"{:}\", color=gray, fontcolor=gray", v.name)),
+            (rate, None) => attrs.push(format!("label=\"{:}\\n{:}\"", v.name, rate)),
+        }
+        if v.name == plan.map(|p| p.start.as_str()).unwrap_or(START) {
+            attrs.push(String::from("shape=box"));
+        }
+        dot += &format!("    {:} [{:}];\n", v.name, attrs.join(", "));
+    }
+
+    for v in valves {
+        // each tunnel is listed from both ends
+        for t in v.tunnels.iter().filter(|t| v.name < **t) {
+            match walked.get(&(v.name.as_str(), t.as_str())) {
+                Some(colour) => {
+                    dot += &format!(
+                        "    {:} -- {:} [color={:}, penwidth=2];\n",
+                        v.name, t, colour
+                    )
+                }
+                None => dot += &format!("    {:} -- {:};\n", v.name, t),
+            }
+        }
+    }
+
+    dot + "}\n"
+}
+
+pub fn solve1(valves: &[Valve]) -> i64 {
+    let net = Network::new(valves, START).unwrap();
+    max_pressure(&net, 30)
+}
+
+pub fn solve2(valves: &[Valve]) -> i64 {
+    let net = Network::new(valves, START).unwrap();
+    max_pressure_pair(&net, 26)
+}
+
+#[test]
+fn test_parse() {
+    assert_eq!(
+        parse_line("Valve HH has flow rate=22; tunnel leads to valve GG"),
+        Ok(Valve {
+            name: String::from("HH"),
+            rate: 22,
+            tunnels: vec![String::from("GG")],
+        })
+    );
+    assert_eq!(
+        parse_line("Valve HH has flow rate=x; tunnel leads to valve GG").map_err(|e| e.column),
+        Err(24)
+    );
+    assert!(parse_line("Valve HH has flow rate=22").is_err());
+
+    let err = parse("Valve AA has flow rate=0; tunnels lead to valves BB, CC\nValve BB has flow rate=1; tunnel leads to valve AA").unwrap_err();
+    assert_eq!((err.line, err.column, err.text.as_str()), (1, 54, "CC"));
+}
+
+#[test]
+fn test_network() {
+    const INPUT: &str = include_str!("../inputs/day16_test.txt");
+    let valves = parse(INPUT).unwrap();
+    let net = Network::new(&valves, START).unwrap();
+
+    assert_eq!(net.useful(), 6);
+    assert_eq!(net.names[net.start()], "AA");
+    // AA -> II -> JJ
+    let jj = net.names.iter().position(|n| n == "JJ").unwrap();
+    assert_eq!(net.dist[net.start()][jj], 2);
+    assert_eq!(Network::new(&valves, "XX"), None);
+}
+
+#[test]
+fn test_solve1() {
+    const INPUT: &str = include_str!("../inputs/day16_test.txt");
+    let parsed = parse(INPUT).unwrap();
+    assert_eq!(solve1(&parsed), 1651);
+}
+
+#[test]
+fn test_solve2() {
+    const INPUT: &str = include_str!("../inputs/day16_test.txt");
+    let parsed = parse(INPUT).unwrap();
+    assert_eq!(solve2(&parsed), 1707);
+}
+
+#[test]
+fn test_plan() {
+    const INPUT: &str = include_str!("../inputs/day16_test.txt");
+    let parsed = parse(INPUT).unwrap();
+
+    let plan = solo_plan(&parsed, 30);
+    assert_eq!(plan.released(), 1651);
+    let order: Vec<&str> = plan.routes[0].iter().map(|o| o.valve.as_str()).collect();
+    assert_eq!(order, ["DD", "BB", "JJ", "HH", "EE", "CC"]);
+    assert_eq!(plan.routes[0][0].minute, 2);
+    assert_eq!(plan.routes[0][0].released, 20 * 28);
+    assert_eq!(plan.routes[0][2].path, ["BB", "AA", "II", "JJ"]);
+
+    let plan = pair_plan(&parsed, 26);
+    assert_eq!(plan.released(), 1707);
+    assert_eq!(plan.routes.len(), 2);
+}
+
+#[test]
+fn test_to_dot() {
+    const INPUT: &str = include_str!("../inputs/day16_test.txt");
+    let parsed = parse(INPUT).unwrap();
+
+    let dot = to_dot(&parsed, None);
+    assert!(dot.starts_with(
+        "graph valves {\n    AA [label=\"AA\", color=gray, fontcolor=gray, shape=box];\n"
+    ));
+    assert!(dot.contains("    BB [label=\"BB\\n13\"];\n"));
+    assert!(dot.contains("    AA -- BB;\n"));
+    // one edge per tunnel
+    assert_eq!(dot.matches(" -- ").count(), 10);
+
+    let plan = solo_plan(&parsed, 30);
+    let dot = to_dot(&parsed, Some(&plan));
+    assert!(dot.contains("    DD [label=\"DD\\n20\\nminute 2\", style=filled, fillcolor=red];\n"));
+    assert!(dot.contains("    AA -- DD [color=red, penwidth=2];\n"));
+    assert!(dot.contains("    FF -- GG [color=red, penwidth=2];\n"));
+}
+
+#[test]
+fn test_agents() {
+    const INPUT: &str = include_str!("../inputs/day16_test.txt");
+    let parsed = parse(INPUT).unwrap();
+    let released = |start, time, agents| plan(&parsed, start, time, agents).map(|p| p.released());
+
+    assert_eq!(released("AA", 30, 1), Some(1651));
+    assert_eq!(released("AA", 26, 2), Some(1707));
+    assert_eq!(released("AA", 26, 0), Some(0));
+    assert_eq!(released("XX", 26, 1), None);
+
+    // more agents never do worse, and six can open everything at once
+    let three = released("AA", 26, 3).unwrap();
+    assert!(three >= 1707);
+    assert!(released("AA", 26, 4).unwrap() >= three);
+    assert_eq!(
+        plan(&parsed, "AA", 26, 6)
+            .unwrap()
+            .routes
+            .iter()
+            .filter(|r| r.len() == 1)
+            .count(),
+        6
+    );
+
+    // starting on a valve still takes a minute to open it
+    let from_jj = plan(&parsed, "JJ", 10, 1).unwrap();
+    assert_eq!(from_jj.start, "JJ");
+    assert_eq!(from_jj.routes[0][0].valve, "JJ");
+    assert_eq!(from_jj.routes[0][0].released, 21 * 9);
+}
@@ -1,16 +1,31 @@
-fn main() {
-    const INPUT: &str = include_str!("../inputs/day2.txt");
-    let input = parse_input(INPUT);
+use crate::error::{parse_lines, ParseError};
+use crate::solution::Solution;
 
-    println!("Day 2 solution 1: {:?}", solve1(&input));
-    println!("Day 2 solution 2: {:?}", solve2(&input));
+pub struct Day2;
+
+impl Solution for Day2 {
+    type Input = Vec<(char, char)>;
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_input(input)
+    }
+
+    fn part1(input: &Self::Input) -> i64 {
+        solve1(input)
+    }
+
+    fn part2(input: &Self::Input) -> i64 {
+        solve2(input)
+    }
 }
 
-fn solve1(input: &[(char, char)]) -> i64 {
+pub fn solve1(input: &[(char, char)]) -> i64 {
     input.iter().map(score1).sum()
 }
 
-fn solve2(input: &[(char, char)]) -> i64 {
+pub fn solve2(input: &[(char, char)]) -> i64 {
     input.iter().map(score2).sum()
 }
 
@@ -50,31 +65,45 @@ fn score2((a, b): &(char, char)) -> i64 {
     shape_score + win_score
 }
 
-fn parse_line(line_str: &str) -> Option<(char, char)> {
-    let split: Vec<&str> = line_str.split(' ').collect();
+fn parse_line(line_str: &str) -> Result<(char, char), ParseError> {
+    let (a, b) = line_str
+        .split_once(' ')
+        .ok_or_else(|| ParseError::new(line_str, line_str, "expected two moves, found"))?;
+
+    Ok((
+        parse_move(line_str, a, "ABC")?,
+        parse_move(line_str, b, "XYZ")?,
+    ))
+}
 
-    Some((split[0].chars().next()?, split[1].chars().next()?))
+fn parse_move(line_str: &str, s: &str, valid: &str) -> Result<char, ParseError> {
+    match s.chars().next() {
+        Some(c) if s.len() == 1 && valid.contains(c) => Ok(c),
+        _ => Err(ParseError::new(line_str, s, "unknown move")),
+    }
 }
 
-fn parse_input(input_str: &str) -> Vec<(char, char)> {
-    input_str.lines().filter_map(parse_line).collect()
+pub fn parse_input(input_str: &str) -> Result<Vec<(char, char)>, ParseError> {
+    parse_lines(input_str, parse_line)
 }
 
 #[test]
 fn test_parser() {
-    assert_eq!(parse_line("A X"), Some(('A', 'X')));
+    assert_eq!(parse_line("A X"), Ok(('A', 'X')));
+    assert_eq!(parse_line("A W").map_err(|e| e.column), Err(3));
+    assert!(parse_line("AX").is_err());
 
     const TEST_INPUT: &str = include_str!("../inputs/day2_test.txt");
     let expected = vec![('A', 'Y'), ('B', 'X'), ('C', 'Z')];
 
-    assert_eq!(parse_input(TEST_INPUT), expected);
+    assert_eq!(parse_input(TEST_INPUT), Ok(expected));
 }
 
 #[test]
 fn test_solution() {
     const TEST_INPUT: &str = include_str!("../inputs/day2_test.txt");
 
-    let input = parse_input(TEST_INPUT);
+    let input = parse_input(TEST_INPUT).unwrap();
     assert_eq!(solve1(&input), 15);
 }
 
@@ -82,6 +111,6 @@ fn test_solution() {
 fn test_solution2() {
     const TEST_INPUT: &str = include_str!("../inputs/day2_test.txt");
 
-    let input = parse_input(TEST_INPUT);
+    let input = parse_input(TEST_INPUT).unwrap();
     assert_eq!(solve2(&input), 12);
 }
@@ -1,23 +1,56 @@
+use crate::error::{parse_lines, ParseError};
+use crate::solution::Solution;
 use std::collections::HashSet;
 
-fn main() {
-    const INPUT: &str = include_str!("../inputs/day3.txt");
+pub struct Day3;
 
-    let input: Vec<&str> = INPUT.lines().collect();
+impl Solution for Day3 {
+    type Input = Vec<String>;
+    type Answer1 = i64;
+    type Answer2 = i64;
 
-    println!("Solution 1: {:?}", solve1(input.as_slice()));
-    println!("Solution 2: {:?}", solve2(input.as_slice()));
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> i64 {
+        solve1(input)
+    }
+
+    fn part2(input: &Self::Input) -> i64 {
+        solve2(input)
+    }
+}
+
+pub fn parse(s: &str) -> Result<Vec<String>, ParseError> {
+    parse_lines(s, parse_line)
 }
 
-fn solve1(sacks: &[&str]) -> i64 {
+// A rucksack is an even number of items, each a letter
+fn parse_line(s: &str) -> Result<String, ParseError> {
+    if let Some((i, c)) = s.char_indices().find(|(_, c)| !c.is_ascii_alphabetic()) {
+        return Err(ParseError::new(s, &s[i..i + c.len_utf8()], "unknown item"));
+    }
+    if !s.len().is_multiple_of(2) {
+        return Err(ParseError::new(
+            s,
+            s,
+            "expected an even number of items, found",
+        ));
+    }
+
+    Ok(String::from(s))
+}
+
+pub fn solve1<S: AsRef<str>>(sacks: &[S]) -> i64 {
     sacks
         .iter()
-        .map(|s| rucksack_priority(s.chars().collect()))
+        .map(|s| rucksack_priority(s.as_ref().chars().collect()))
         .sum()
 }
 
-fn solve2(sacks: &[&str]) -> i64 {
-    let char_vecs: Vec<Vec<char>> = sacks.to_vec().iter().map(|s| s.chars().collect()).collect();
+pub fn solve2<S: AsRef<str>>(sacks: &[S]) -> i64 {
+    let char_vecs: Vec<Vec<char>> = sacks.iter().map(|s| s.as_ref().chars().collect()).collect();
     let groups = char_vecs.chunks(3);
     let priorities = groups.map(|g| group_priority(g.to_vec()));
     priorities.sum()
@@ -35,30 +68,25 @@ fn find_common(a: Vec<char>, b: Vec<char>) -> Option<char> {
     let set_a: HashSet<char> = a.into_iter().collect();
     let set_b: HashSet<char> = b.into_iter().collect();
 
-    let common: Vec<&char> = set_a.intersection(&set_b).collect();
-
-    Some(*common[0])
+    set_a.intersection(&set_b).next().copied()
 }
 
+// Groups that are missing a rucksack or share no item are worth 0
 fn group_priority(group: Vec<Vec<char>>) -> i64 {
-    let a = &group[0];
-    let b = &group[1];
-    let c = &group[2];
-
-    find_common_3(a, b, c).map(priority).unwrap_or(0)
+    match group.as_slice() {
+        [a, b, c] => find_common_3(a, b, c).map(priority).unwrap_or(0),
+        _ => 0,
+    }
 }
 
-fn find_common_3(a: &Vec<char>, b: &Vec<char>, c: &Vec<char>) -> Option<char> {
-    let set2: HashSet<char> = b.clone().into_iter().collect();
-    let set3: HashSet<char> = c.clone().into_iter().collect();
+fn find_common_3(a: &[char], b: &[char], c: &[char]) -> Option<char> {
+    let set2: HashSet<char> = b.iter().copied().collect();
+    let set3: HashSet<char> = c.iter().copied().collect();
     let sets = [&set2, &set3];
 
-    let common: Vec<&char> = a
-        .iter()
-        .filter(|k| sets.iter().all(|s| s.contains(k)))
-        .collect();
-
-    Some(*common[0])
+    a.iter()
+        .find(|k| sets.iter().all(|s| s.contains(k)))
+        .copied()
 }
 
 fn split_bags(items: Vec<char>) -> (Vec<char>, Vec<char>) {
@@ -84,6 +112,19 @@ fn test_priority() {
     assert_eq!(priority('s'), 19);
 }
 
+#[test]
+fn test_parse() {
+    assert_eq!(
+        parse("abCD\nxy"),
+        Ok(vec![String::from("abCD"), String::from("xy")])
+    );
+    assert_eq!(
+        parse("abCD\nx1").map_err(|e| (e.line, e.column)),
+        Err((2, 2))
+    );
+    assert!(parse("abc").is_err());
+}
+
 #[test]
 fn test_solve1() {
     const TEST_INPUT: &str = include_str!("../inputs/day3_test.txt");
@@ -92,13 +133,23 @@ fn test_solve1() {
     assert_eq!(solve1(input.as_slice()), 157);
 }
 
+#[test]
+fn test_no_common_item() {
+    assert_eq!(find_common(vec!['a', 'b'], vec!['c', 'd']), None);
+    assert_eq!(find_common_3(&['a'], &['b'], &['a']), None);
+    assert_eq!(solve1(&["abcd"]), 0);
+    assert_eq!(solve2(&["ab", "ab", "cd", "ab"]), 0);
+}
+
 #[test]
 fn test_find_common_3() {
     assert_eq!(
         find_common_3(
-            &"vJrwpWtwJgWrhcsFMMfFFhFp".chars().collect(),
-            &"jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL".chars().collect(),
-            &"PmmdzqPrVvPwwTWBwg".chars().collect(),
+            &"vJrwpWtwJgWrhcsFMMfFFhFp".chars().collect::<Vec<_>>(),
+            &"jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL"
+                .chars()
+                .collect::<Vec<_>>(),
+            &"PmmdzqPrVvPwwTWBwg".chars().collect::<Vec<_>>(),
         ),
         Some('r'),
     );
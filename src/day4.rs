@@ -1,33 +1,54 @@
-fn main() {
-    const INPUT: &str = include_str!("../inputs/day4.txt");
-    let input: Vec<(Range, Range)> = INPUT.lines().map(parse_line).collect();
-    println!("Solution 1: {:?}", solve1(&input));
-    println!("Solution 2: {:?}", solve2(&input));
+use crate::error::{parse_lines, parse_num, ParseError};
+use crate::solution::Solution;
+
+pub struct Day4;
+
+impl Solution for Day4 {
+    type Input = Vec<(Range, Range)>;
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> i64 {
+        solve1(input)
+    }
+
+    fn part2(input: &Self::Input) -> i64 {
+        solve2(input)
+    }
 }
 
-fn solve1(input: &Vec<(Range, Range)>) -> i64 {
+pub fn solve1(input: &[(Range, Range)]) -> i64 {
     input.iter().filter(|(a, b)| either_includes(a, b)).count() as i64
 }
 
-fn solve2(input: &Vec<(Range, Range)>) -> i64 {
+pub fn solve2(input: &[(Range, Range)]) -> i64 {
     input.iter().filter(|(a, b)| overlaps(a, b)).count() as i64
 }
 
-type Range = (i64, i64);
+pub type Range = (i64, i64);
 
-fn parse_line(s: &str) -> (Range, Range) {
-    let s2: Vec<&str> = s.split(',').collect();
+pub fn parse(s: &str) -> Result<Vec<(Range, Range)>, ParseError> {
+    parse_lines(s, parse_line)
+}
 
-    assert!(s2.len() == 2);
+pub fn parse_line(s: &str) -> Result<(Range, Range), ParseError> {
+    let (a, b) = s
+        .split_once(',')
+        .ok_or_else(|| ParseError::new(s, s, "expected two ranges, found"))?;
 
-    (parse_range(s2[0]), parse_range(s2[1]))
+    Ok((parse_range(s, a)?, parse_range(s, b)?))
 }
 
-fn parse_range(s: &str) -> Range {
-    let s2: Vec<&str> = s.split('-').collect();
-    assert!(s2.len() == 2);
+fn parse_range(line: &str, s: &str) -> Result<Range, ParseError> {
+    let (a, b) = s
+        .split_once('-')
+        .ok_or_else(|| ParseError::new(line, s, "expected a range, found"))?;
 
-    (s2[0].parse().unwrap(), s2[1].parse().unwrap())
+    Ok((parse_num(line, a)?, parse_num(line, b)?))
 }
 
 fn either_includes(a: &Range, b: &Range) -> bool {
@@ -45,7 +66,7 @@ fn overlaps((a, b): &Range, (x, y): &Range) -> bool {
 #[test]
 fn test_solve1() {
     const TEST_INPUT: &str = include_str!("../inputs/day4_test.txt");
-    let input: Vec<(Range, Range)> = TEST_INPUT.lines().map(parse_line).collect();
+    let input = parse(TEST_INPUT).unwrap();
 
     assert_eq!(solve1(&input), 2);
 }
@@ -53,12 +74,14 @@ fn test_solve1() {
 #[test]
 fn test_solve2() {
     const TEST_INPUT: &str = include_str!("../inputs/day4_test.txt");
-    let input: Vec<(Range, Range)> = TEST_INPUT.lines().map(parse_line).collect();
+    let input = parse(TEST_INPUT).unwrap();
 
     assert_eq!(solve2(&input), 4);
 }
 
 #[test]
 fn test_parse_line() {
-    assert_eq!(parse_line("2-4,6-8"), ((2, 4), (6, 8)));
+    assert_eq!(parse_line("2-4,6-8"), Ok(((2, 4), (6, 8))));
+    assert_eq!(parse_line("2-4,6-x").map_err(|e| e.column), Err(7));
+    assert!(parse_line("2-4").is_err());
 }
@@ -1,20 +1,30 @@
-type State = Vec<Vec<char>>;
-type Move = (usize, usize, usize); // n, from, to
-
-fn main() {
-    const INPUT: &str = include_str!("../inputs/day5.txt");
-    let input = parse_input(INPUT);
-    println!(
-        "Solution 1: {:}",
-        solve1(input.clone()).into_iter().collect::<String>()
-    );
-    println!(
-        "Solution 2: {:}",
-        solve2(input).into_iter().collect::<String>()
-    );
+use crate::error::{parse_lines, parse_num, ParseError};
+use crate::solution::Solution;
+
+pub type State = Vec<Vec<char>>;
+pub type Move = (usize, usize, usize); // n, from, to
+
+pub struct Day5;
+
+impl Solution for Day5 {
+    type Input = (State, Vec<Move>);
+    type Answer1 = String;
+    type Answer2 = String;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_input(input)
+    }
+
+    fn part1(input: &Self::Input) -> String {
+        solve1(input.clone()).into_iter().collect()
+    }
+
+    fn part2(input: &Self::Input) -> String {
+        solve2(input.clone()).into_iter().collect()
+    }
 }
 
-fn solve1((state, moves): (State, Vec<Move>)) -> Vec<char> {
+pub fn solve1((state, moves): (State, Vec<Move>)) -> Vec<char> {
     let final_state: State = moves.iter().fold(state, |s, &m| do_move(s, m));
 
     final_state
@@ -33,51 +43,81 @@ fn do_move(mut state: State, (n, from, to): Move) -> State {
     state
 }
 
-fn parse_input(s: &str) -> (State, Vec<Move>) {
-    let sections: Vec<&str> = s.split("\n\n").collect();
-
-    (parse_state(sections[0]), parse_moves(sections[1]))
+pub fn parse_input(s: &str) -> Result<(State, Vec<Move>), ParseError> {
+    let (state_s, moves_s) = s.split_once("\n\n").ok_or_else(|| {
+        let last_line = s.lines().last().unwrap_or("");
+        ParseError::new(
+            last_line,
+            last_line,
+            "expected a blank line and moves after",
+        )
+        .offset(s.lines().count().saturating_sub(1))
+    })?;
+
+    let state = parse_state(state_s)?;
+    let moves =
+        parse_moves(moves_s, state.len()).map_err(|e| e.offset(state_s.lines().count() + 1))?;
+
+    Ok((state, moves))
 }
 
-fn parse_state(s: &str) -> State {
-    let ls: Vec<&str> = s.lines().rev().collect();
-
-    let parsed_lines: Vec<_> = ls[1..].iter().map(|l| parse_state_line(l)).collect();
+// The crate rows from top to bottom followed by the row of stack numbers
+fn parse_state(s: &str) -> Result<State, ParseError> {
+    let ls: Vec<&str> = s.lines().collect();
+    let numbers = ls.last().copied().unwrap_or("");
+    let stacks = numbers.split_whitespace().count();
+    if stacks == 0 {
+        return Err(
+            ParseError::new(numbers, numbers, "expected stack numbers, found")
+                .offset(ls.len().saturating_sub(1)),
+        );
+    }
 
-    let mut state: State = vec![vec![]; parsed_lines[0].len()];
+    let mut state: State = vec![vec![]; stacks];
 
-    parsed_lines.into_iter().for_each(|line| {
-        line.iter().enumerate().into_iter().for_each(|(i, c)| {
-            if *c != ' ' {
-                state[i].push(*c)
+    for (y, line) in ls[..ls.len() - 1].iter().enumerate().rev() {
+        for (i, c) in parse_state_line(line).into_iter().enumerate() {
+            if c == ' ' {
+                continue;
             }
-        });
-    });
+            if i >= stacks || !c.is_ascii_uppercase() {
+                let column = 1 + i * 4;
+                return Err(
+                    ParseError::new(line, &line[column..column + 1], "unexpected crate").offset(y),
+                );
+            }
+            state[i].push(c)
+        }
+    }
 
-    state
+    Ok(state)
 }
 
 fn parse_state_line(s: &str) -> Vec<char> {
     s.chars().skip(1).step_by(4).collect()
 }
 
-fn parse_moves(s: &str) -> Vec<Move> {
-    s.lines().map(|l| parse_move(l)).collect()
+fn parse_moves(s: &str, stacks: usize) -> Result<Vec<Move>, ParseError> {
+    parse_lines(s, |l| parse_move(l, stacks))
 }
 
-fn parse_move(s: &str) -> Move {
+// "move n from a to b", with the stacks numbered from 1
+fn parse_move(s: &str, stacks: usize) -> Result<Move, ParseError> {
     let v: Vec<&str> = s.split(' ').collect();
+    if v.len() != 6 || v[0] != "move" || v[2] != "from" || v[4] != "to" {
+        return Err(ParseError::new(s, s, "expected a move, found"));
+    }
 
-    let (n, to, from) = (
-        v[1].parse().unwrap(),
-        v[3].parse().unwrap(),
-        v[5].parse().unwrap(),
-    );
-    (n, to, from)
+    let stack = |text| match parse_num(s, text)? {
+        n if (1..=stacks).contains(&n) => Ok(n),
+        _ => Err(ParseError::new(s, text, "no such stack")),
+    };
+
+    Ok((parse_num(s, v[1])?, stack(v[3])?, stack(v[5])?))
 }
 
 // star 2:
-fn solve2((state, moves): (State, Vec<Move>)) -> Vec<char> {
+pub fn solve2((state, moves): (State, Vec<Move>)) -> Vec<char> {
     let final_state: State = moves.iter().fold(state, |s, &m| do_move2(s, m));
 
     final_state
@@ -97,7 +137,12 @@ fn do_move2(mut state: State, (n, from, to): Move) -> State {
 
 #[test]
 fn test_parse_move() {
-    assert_eq!(parse_move("move 1 from 2 to 1"), (1, 2, 1));
+    assert_eq!(parse_move("move 1 from 2 to 1", 3), Ok((1, 2, 1)));
+    assert_eq!(
+        parse_move("move 1 from 4 to 1", 3).map_err(|e| e.column),
+        Err(13)
+    );
+    assert!(parse_move("move 1 from 2", 3).is_err());
 }
 
 #[test]
@@ -109,19 +154,22 @@ fn test_parse_state_line() {
 fn test_parse_state() {
     let parsed = parse_state("    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 ");
     let expected = vec![vec!['Z', 'N'], vec!['M', 'C', 'D'], vec!['P']];
-    assert_eq!(parsed, expected)
+    assert_eq!(parsed, Ok(expected));
+
+    let err = parse_state("    [d]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 ").unwrap_err();
+    assert_eq!((err.line, err.column), (1, 6));
 }
 
 #[test]
 fn test_solve1() {
     const INPUT: &str = include_str!("../inputs/day5_test.txt");
-    let input = parse_input(INPUT);
+    let input = parse_input(INPUT).unwrap();
     assert_eq!(solve1(input), vec!['C', 'M', 'Z']);
 }
 
 #[test]
 fn test_solve2() {
     const INPUT: &str = include_str!("../inputs/day5_test.txt");
-    let input = parse_input(INPUT);
+    let input = parse_input(INPUT).unwrap();
     assert_eq!(solve2(input), vec!['M', 'C', 'D']);
 }
@@ -1,16 +1,32 @@
-fn main() {
-    const INPUT: &str = include_str!("../inputs/day6.txt");
+use crate::error::ParseError;
+use crate::solution::Solution;
 
-    println!("Solution 1: {:}", solve1(INPUT));
-    println!("Solution 2: {:}", solve2(INPUT));
+pub struct Day6;
+
+impl Solution for Day6 {
+    type Input = String;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<String, ParseError> {
+        Ok(String::from(input.trim()))
+    }
+
+    fn part1(input: &String) -> usize {
+        solve1(input)
+    }
+
+    fn part2(input: &String) -> usize {
+        solve2(input)
+    }
 }
 
-fn solve1(s: &str) -> usize {
+pub fn solve1(s: &str) -> usize {
     let mut ws = s.as_bytes().windows(4);
     ws.position(|w| all_unique(Vec::from(w))).unwrap() + 4
 }
 
-fn solve2(s: &str) -> usize {
+pub fn solve2(s: &str) -> usize {
     let mut ws = s.as_bytes().windows(14);
     ws.position(|w| all_unique(Vec::from(w))).unwrap() + 14
 }
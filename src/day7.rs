@@ -1,17 +1,28 @@
-fn main() {
-    const INPUT: &str = include_str!("../inputs/day7.txt");
-    let mut lines: std::str::Lines = INPUT.lines();
-    lines.next();
-    let parsed = parse_dir("/", &mut lines).unwrap();
-    let solution1 = solve1(&parsed);
-    let solution2 = solve2(&parsed);
+use crate::error::ParseError;
+use crate::solution::Solution;
 
-    println!("Solution 1: {:}", solution1);
-    println!("Solution 2: {:}", solution2);
+pub struct Day7;
+
+impl Solution for Day7 {
+    type Input = AocFile;
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    fn parse(input: &str) -> Result<AocFile, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &AocFile) -> i64 {
+        solve1(input)
+    }
+
+    fn part2(input: &AocFile) -> i64 {
+        solve2(input)
+    }
 }
 
 #[derive(PartialEq, Debug)]
-enum AocFile {
+pub enum AocFile {
     Dir {
         name: String,
         children: Vec<AocFile>,
@@ -23,7 +34,19 @@ enum AocFile {
     },
 }
 
-fn parse_file(s: &str) -> Option<AocFile> {
+type Lines<'a> = std::iter::Enumerate<std::str::Lines<'a>>;
+
+// Parse a terminal session starting with `$ cd /` into the root directory
+pub fn parse(s: &str) -> Result<AocFile, ParseError> {
+    let mut lines = s.lines().enumerate();
+    match lines.next() {
+        Some((i, l @ "$ cd /")) => parse_dir("/", (i, l), &mut lines),
+        Some((_, l)) => Err(ParseError::new(l, l, "expected \"$ cd /\", found")),
+        None => Err(ParseError::new(s, s, "expected \"$ cd /\", found")),
+    }
+}
+
+pub fn parse_file(s: &str) -> Option<AocFile> {
     let parts: Vec<&str> = s.split(' ').collect();
     let name = String::from(*parts.get(1)?);
     let size = (*parts.first()?).parse().ok()?;
@@ -31,50 +54,51 @@ fn parse_file(s: &str) -> Option<AocFile> {
     Some(AocFile::File { name, size })
 }
 
-fn parse_dir(name: &str, ls: &mut std::str::Lines) -> Option<AocFile> {
+// Parse the directory entered by the `cd` line, numbered from 0
+pub fn parse_dir(name: &str, cd: (usize, &str), ls: &mut Lines) -> Result<AocFile, ParseError> {
     // the next line should be ls, we don't need it but check for sanity
-    let ls_line = ls.next();
-    assert_eq!(ls_line, Some("$ ls"));
+    match ls.next() {
+        Some((_, "$ ls")) => (),
+        Some((i, l)) => return Err(ParseError::new(l, l, "expected \"$ ls\", found").offset(i)),
+        None => {
+            let (i, l) = cd;
+            return Err(ParseError::new(l, l, "expected \"$ ls\" after").offset(i));
+        }
+    }
 
     let mut children: Vec<AocFile> = vec![];
-    loop {
-        let line = ls.next();
-        match line {
-            None => break,
-            Some(l) => {
-                if l.trim() == "$ cd .." {
-                    break;
-                }
-                if l[..3] == *"dir" {
-                    continue;
-                }
-                if l[..4] == *"$ cd" {
-                    let next_name = l.split(' ').nth(2);
-                    children.push(parse_dir(next_name?, ls)?);
-                }
-                if let Some(file) = parse_file(l) {
-                    children.push(file);
-                }
-            }
+    while let Some((i, l)) = ls.next() {
+        if l.trim() == "$ cd .." {
+            break;
+        }
+        if l.starts_with("dir ") {
+            continue;
+        }
+        if let Some(next_name) = l.strip_prefix("$ cd ") {
+            children.push(parse_dir(next_name, (i, l), ls)?);
+        } else if let Some(file) = parse_file(l) {
+            children.push(file);
+        } else {
+            return Err(ParseError::new(l, l, "unexpected output").offset(i));
         }
     }
 
     let size = children.iter().map(file_size).sum();
-    Some(AocFile::Dir {
+    Ok(AocFile::Dir {
         name: String::from(name),
         children,
         size,
     })
 }
 
-fn file_size(f: &AocFile) -> i64 {
+pub fn file_size(f: &AocFile) -> i64 {
     match f {
         AocFile::File { size, .. } => *size,
         AocFile::Dir { size, .. } => *size,
     }
 }
 
-fn flatten_dir(f: &AocFile) -> Vec<&AocFile> {
+pub fn flatten_dir(f: &AocFile) -> Vec<&AocFile> {
     match f {
         AocFile::File { .. } => vec![f],
         AocFile::Dir { children, .. } => {
@@ -87,8 +111,8 @@ fn flatten_dir(f: &AocFile) -> Vec<&AocFile> {
     }
 }
 
-fn solve1(root: &AocFile) -> i64 {
-    flatten_dir(&root)
+pub fn solve1(root: &AocFile) -> i64 {
+    flatten_dir(root)
         .iter()
         .filter(|f| is_dir(f))
         .map(|d| file_size(d))
@@ -96,11 +120,11 @@ fn solve1(root: &AocFile) -> i64 {
         .sum()
 }
 
-fn is_dir(f: &AocFile) -> bool {
+pub fn is_dir(f: &AocFile) -> bool {
     matches!(f, AocFile::Dir { .. })
 }
 
-fn solve2(root: &AocFile) -> i64 {
+pub fn solve2(root: &AocFile) -> i64 {
     let total_space: i64 = 70000000;
     let used = file_size(root);
     let free = total_space - used;
@@ -136,46 +160,57 @@ fn test_sanity() {
     assert!(a == b);
 
     let mut l = "$cd ..\n".lines();
-    match l.next() {
-        Some(s) => {
-            assert!(s == "$cd ..")
-        }
-        None => (),
+    if let Some(s) = l.next() {
+        assert!(s == "$cd ..")
     }
 }
 
 #[test]
 fn test_parse_dir() {
     const INPUT: &str = include_str!("../inputs/day7_test.txt");
-    let mut lines: std::str::Lines = INPUT.lines();
-    lines.next();
-    let parsed = parse_dir("/", &mut lines);
-    if let Some(AocFile::Dir { children, size, .. }) = parsed {
+    let parsed = parse(INPUT);
+    if let Ok(AocFile::Dir { children, size, .. }) = parsed {
         assert_eq!(children.len(), 4);
         assert_eq!(size, 48381165);
     } else {
-        assert!(false);
+        panic!("expected a directory, got {:?}", parsed);
     }
 }
 
+#[test]
+fn test_parse_errors() {
+    let err = parse("$ cd /\n$ ls\n123 a\nfoo bar baz").unwrap_err();
+    assert_eq!(
+        (err.line, err.column, err.text.as_str()),
+        (4, 1, "foo bar baz")
+    );
+
+    let err = parse("$ cd /\n$ ls\n$ cd a\n$ cd b").unwrap_err();
+    assert_eq!((err.line, err.text.as_str()), (4, "$ cd b"));
+
+    assert!(parse("$ ls").is_err());
+
+    let err = parse("$ cd /\n$ ls\n$ cd a").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "line 3, column 1: expected \"$ ls\" after \"$ cd a\""
+    );
+}
+
 #[test]
 fn test_flatten() {
     const INPUT: &str = include_str!("../inputs/day7_test.txt");
-    let mut lines: std::str::Lines = INPUT.lines();
-    lines.next();
-    let parsed = parse_dir("/", &mut lines);
+    let parsed = parse(INPUT);
 
     let flat = parsed.as_ref().map(flatten_dir);
 
-    assert_eq!(flat.map(|v| v.len()), Some(14));
+    assert_eq!(flat.map(|v| v.len()), Ok(14));
 }
 
 #[test]
 fn test_solve1() {
     const INPUT: &str = include_str!("../inputs/day7_test.txt");
-    let mut lines: std::str::Lines = INPUT.lines();
-    lines.next();
-    let parsed = parse_dir("/", &mut lines).unwrap();
+    let parsed = parse(INPUT).unwrap();
     let solution = solve1(&parsed);
 
     assert_eq!(solution, 95437);
@@ -184,9 +219,7 @@ fn test_solve1() {
 #[test]
 fn test_solve2() {
     const INPUT: &str = include_str!("../inputs/day7_test.txt");
-    let mut lines: std::str::Lines = INPUT.lines();
-    lines.next();
-    let parsed = parse_dir("/", &mut lines).unwrap();
+    let parsed = parse(INPUT).unwrap();
     let solution = solve2(&parsed);
 
     assert_eq!(solution, 24933642);
@@ -1,142 +1,109 @@
-use itertools::iproduct;
+use crate::error::ParseError;
+use crate::grid::Grid;
+use crate::point::{Point, DIRS4};
+use crate::solution::Solution;
 
-type Forest = Vec<Vec<i64>>;
+pub type Forest = Grid<i64>;
 
-fn main() {
-    const INPUT: &str = include_str!("../inputs/day8.txt");
-    let parsed = parse(INPUT);
-    let solution1 = solve1(&parsed);
-    let solution2 = solve2(&parsed);
+pub struct Day8;
 
-    println!("Solution 1: {:}", solution1);
-    println!("Solution 2: {:}", solution2);
-}
-
-fn parse(s: &str) -> Forest {
-    s.lines().map(parse_line).collect()
-}
-
-fn parse_line(s: &str) -> Vec<i64> {
-    s.chars().map(|c| c.to_string().parse().unwrap()).collect()
-}
-
-fn visible(f: &Forest, x: usize, y: usize) -> bool {
-    let h: i64 = f[y][x];
+impl Solution for Day8 {
+    type Input = Forest;
+    type Answer1 = i64;
+    type Answer2 = i64;
 
-    let height = f.len();
-    let width = f[0].len();
-
-    if x == 0 || y == 0 || x == width - 1 || y == height - 1 {
-        return true;
+    fn parse(input: &str) -> Result<Forest, ParseError> {
+        parse(input)
     }
 
-    let from_left = (0..x).all(|i| f[y][i] < h);
-    let from_right = ((x + 1)..width).all(|i| f[y][i] < h);
+    fn part1(input: &Forest) -> i64 {
+        solve1(input)
+    }
 
-    let from_top = (0..y).all(|i| f[i][x] < h);
-    let from_bottom = (y + 1..height).all(|i| f[i][x] < h);
+    fn part2(input: &Forest) -> i64 {
+        solve2(input)
+    }
+}
 
-    from_left || from_right || from_top || from_bottom
+pub fn parse(s: &str) -> Result<Forest, ParseError> {
+    Grid::parse(s, |c| {
+        c.to_digit(10)
+            .map(i64::from)
+            .ok_or("expected a tree height, found")
+    })
 }
 
-fn solve1(f: &Forest) -> i64 {
-    let height = f.len();
-    let width = f[0].len();
+pub fn visible(f: &Forest, p: Point) -> bool {
+    let h: i64 = f[p];
 
-    iproduct!((0..height), (0..width))
-        .filter(|(y, x)| visible(f, *x, *y))
-        .count() as i64
+    DIRS4.iter().any(|dir| f.ray(p, *dir).all(|t| f[t] < h))
 }
 
-fn scenic_score(f: &Forest, x: usize, y: usize) -> i64 {
-    let h: i64 = f[y][x];
-
-    let height = f.len();
-    let width = f[0].len();
-
-    println!("h: {:}", h);
-
-    // unwrap_or ei palauta unwrap_or koko homman pituus...
-    let left = x;
-    let right = width - x - 1;
-    let top = y;
-    let bottom = height - y - 1;
-
-    let to_left = (0..x)
-        .rev()
-        .position(|i| f[y][i] >= h)
-        .map(|x| x + 1)
-        .unwrap_or(left);
-    let to_right = ((x + 1)..width)
-        .position(|i| f[y][i] >= h)
-        .map(|x| x + 1)
-        .unwrap_or(right);
-
-    let sln = (y + 1..height).position(|i| f[i][x] >= h);
-    let asd: Vec<_> = ((x + 1)..width).collect();
-    println!("is: {:?} heights: {:?}", asd, sln);
-
-    let to_top = (0..y)
-        .rev()
-        .position(|i| f[i][x] >= h)
-        .map(|x| x + 1)
-        .unwrap_or(top);
-    let to_bottom = (y + 1..height)
-        .position(|i| f[i][x] >= h)
-        .map(|x| x + 1)
-        .unwrap_or(bottom);
-
-    println!("{:}, {:}, {:}, {:}", to_left, to_right, to_top, to_bottom);
-
-    (to_left * to_right * to_top * to_bottom) as i64
+pub fn solve1(f: &Forest) -> i64 {
+    f.positions().filter(|p| visible(f, *p)).count() as i64
 }
 
-fn solve2(f: &Forest) -> i64 {
-    let height = f.len();
-    let width = f[0].len();
+pub fn scenic_score(f: &Forest, p: Point) -> i64 {
+    let h: i64 = f[p];
+
+    DIRS4
+        .iter()
+        .map(|dir| {
+            // the view stops at the first tree at least as high, or the edge
+            let ray: Vec<Point> = f.ray(p, *dir).collect();
+            ray.iter()
+                .position(|t| f[*t] >= h)
+                .map(|i| i + 1)
+                .unwrap_or(ray.len())
+        })
+        .product::<usize>() as i64
+}
 
-    iproduct!((0..height), (0..width))
-        .map(|(y, x)| scenic_score(f, x, y))
-        .max()
-        .unwrap_or(0) as i64
+pub fn solve2(f: &Forest) -> i64 {
+    f.positions().map(|p| scenic_score(f, p)).max().unwrap_or(0)
 }
 
 #[test]
 fn test_parse() {
     const INPUT: &str = include_str!("../inputs/day8_test.txt");
-    let parsed = parse(INPUT);
+    let parsed = parse(INPUT).unwrap();
 
-    let expected = vec![
+    let expected = Grid::from_rows(vec![
         vec![3, 0, 3, 7, 3],
         vec![2, 5, 5, 1, 2],
         vec![6, 5, 3, 3, 2],
         vec![3, 3, 5, 4, 9],
         vec![3, 5, 3, 9, 0],
-    ];
+    ])
+    .unwrap();
 
     assert_eq!(parsed, expected);
+
+    let err = parse("123\n4x6").unwrap_err();
+    assert_eq!((err.line, err.column, err.text.as_str()), (2, 2, "x"));
+    assert_eq!(parse("123\n45").map_err(|e| e.line), Err(2));
 }
 
 #[test]
 fn test_visible() {
     const INPUT: &str = include_str!("../inputs/day8_test.txt");
-    let f = parse(INPUT);
+    let f = parse(INPUT).unwrap();
 
-    assert!(visible(&f, 1, 1));
-    assert!(!visible(&f, 2, 2));
-    assert!(visible(&f, 0, 0));
+    assert!(visible(&f, Point::new(1, 1)));
+    assert!(!visible(&f, Point::new(2, 2)));
+    assert!(visible(&f, Point::new(0, 0)));
 
-    assert!(visible(&f, 1, 1));
-    assert!(visible(&f, 2, 1));
-    assert!(visible(&f, 1, 2));
-    assert!(visible(&f, 3, 2));
-    assert!(visible(&f, 2, 3));
+    assert!(visible(&f, Point::new(1, 1)));
+    assert!(visible(&f, Point::new(2, 1)));
+    assert!(visible(&f, Point::new(1, 2)));
+    assert!(visible(&f, Point::new(3, 2)));
+    assert!(visible(&f, Point::new(2, 3)));
 }
 
 #[test]
 fn test_solve1() {
     const INPUT: &str = include_str!("../inputs/day8_test.txt");
-    let parsed = parse(INPUT);
+    let parsed = parse(INPUT).unwrap();
     let solution = solve1(&parsed);
 
     assert_eq!(solution, 21);
@@ -145,18 +112,18 @@ fn test_solve1() {
 #[test]
 fn test_scenic_height() {
     const INPUT: &str = include_str!("../inputs/day8_test.txt");
-    let parsed = parse(INPUT);
-    let score = scenic_score(&parsed, 2, 1);
+    let parsed = parse(INPUT).unwrap();
+    let score = scenic_score(&parsed, Point::new(2, 1));
 
     assert_eq!(score, 4);
 
-    assert_eq!(scenic_score(&parsed, 2, 3), 8);
+    assert_eq!(scenic_score(&parsed, Point::new(2, 3)), 8);
 }
 
 #[test]
 fn test_solve2() {
     const INPUT: &str = include_str!("../inputs/day8_test.txt");
-    let parsed = parse(INPUT);
+    let parsed = parse(INPUT).unwrap();
     let solution = solve2(&parsed);
 
     assert_eq!(solution, 8);
@@ -1,72 +1,76 @@
-use num::{abs, signum};
-use std::cmp;
+use crate::error::{parse_lines, parse_num, ParseError};
+use crate::point::{Dir, Point};
+use crate::solution::Solution;
 use std::collections::HashSet;
 
-#[derive(Clone, Debug)]
-enum Step {
-    L,
-    R,
-    U,
-    D,
-}
+pub type Step = Dir;
+pub type Coord = Point;
+pub type State = (Coord, Coord); // head, tail
+pub type State10 = [Coord; 10];
+
+pub struct Day9;
+
+impl Solution for Day9 {
+    type Input = Vec<Step>;
+    type Answer1 = i64;
+    type Answer2 = i64;
 
-type Coord = (i64, i64); // x, y
-type State = (Coord, Coord); // head, tail
-type State10 = [Coord; 10];
+    fn parse(input: &str) -> Result<Vec<Step>, ParseError> {
+        parse(input)
+    }
 
-fn main() {
-    const INPUT: &str = include_str!("../inputs/day9.txt");
-    let parsed = parse(INPUT);
-    let solution1 = solve1(parsed.clone());
-    let solution2 = solve2(parsed);
+    fn part1(input: &Vec<Step>) -> i64 {
+        solve1(input.clone())
+    }
 
-    println!("Solution 1: {:}", solution1);
-    println!("Solution 2: {:}", solution2);
+    fn part2(input: &Vec<Step>) -> i64 {
+        solve2(input.clone())
+    }
 }
 
-fn solve1(steps: Vec<Step>) -> i64 {
-    let initial = ((0, 0), (0, 0));
+pub fn solve1(steps: Vec<Step>) -> i64 {
+    let initial = (Point::ORIGIN, Point::ORIGIN);
     let states = run(initial, steps);
-    let tails: HashSet<(i64, i64)> = states.into_iter().map(|(_, t)| t).collect();
+    let tails: HashSet<Coord> = states.into_iter().map(|(_, t)| t).collect();
 
     tails.len() as i64
 }
 
-fn solve2(steps: Vec<Step>) -> i64 {
-    let initial = [(0, 0); 10];
+pub fn solve2(steps: Vec<Step>) -> i64 {
+    let initial = [Point::ORIGIN; 10];
     let states = run10(initial, steps);
     let tails: HashSet<_> = states.into_iter().map(|s| s[9]).collect();
 
     tails.len() as i64
 }
 
-fn run(initial: State, steps: Vec<Step>) -> Vec<State> {
+pub fn run(initial: State, steps: Vec<Step>) -> Vec<State> {
     steps
         .iter()
         .scan(initial, |state, step| {
-            let next_step = do_step(*state, step.clone());
+            let next_step = do_step(*state, *step);
             *state = next_step;
             Some(next_step)
         })
         .collect()
 }
 
-fn parse(s: &str) -> Vec<Step> {
-    let ls = s.lines();
-    ls.flat_map(parse_line).collect()
+pub fn parse(s: &str) -> Result<Vec<Step>, ParseError> {
+    let steps = parse_lines(s, parse_line)?;
+    Ok(steps.into_iter().flatten().collect())
 }
 
-fn parse_line(s: &str) -> Vec<Step> {
-    let parts: Vec<&str> = s.split(' ').collect();
-    let step = match parts[0] {
-        "L" => Step::L,
-        "R" => Step::R,
-        "U" => Step::U,
-        "D" => Step::D,
-        _ => panic!("Failed parsing line {:?}", s),
+fn parse_line(s: &str) -> Result<Vec<Step>, ParseError> {
+    let (dir, count) = s
+        .split_once(' ')
+        .ok_or_else(|| ParseError::new(s, s, "expected a direction and a count, found"))?;
+    let mut chars = dir.chars();
+    let step = match (chars.next().and_then(Dir::from_letter), chars.next()) {
+        (Some(step), None) => step,
+        _ => return Err(ParseError::new(s, dir, "unknown direction")),
     };
-    let count = parts[1].parse().unwrap();
-    vec![step; count]
+    let count = parse_num(s, count)?;
+    Ok(vec![step; count])
 }
 
 fn do_step(state: State, step: Step) -> State {
@@ -78,7 +82,7 @@ fn do_step(state: State, step: Step) -> State {
 }
 
 fn do_step10(state: State10, step: Step) -> State10 {
-    let mut new_state = [(0, 0); 10];
+    let mut new_state = [Point::ORIGIN; 10];
     new_state[0] = move_head(state[0], step);
 
     for i in 1..10 {
@@ -87,64 +91,49 @@ fn do_step10(state: State10, step: Step) -> State10 {
     new_state
 }
 
-fn run10(initial: State10, steps: Vec<Step>) -> Vec<State10> {
+pub fn run10(initial: State10, steps: Vec<Step>) -> Vec<State10> {
     steps
         .iter()
         .scan(initial, |state, step| {
-            let next_step = do_step10(*state, step.clone());
+            let next_step = do_step10(*state, *step);
             *state = next_step;
             Some(next_step)
         })
         .collect()
 }
 
-fn move_head((hx, hy): (i64, i64), s: Step) -> (i64, i64) {
-    match s {
-        Step::L => (hx - 1, hy),
-        Step::R => (hx + 1, hy),
-        Step::U => (hx, hy + 1),
-        Step::D => (hx, hy - 1),
-    }
+fn move_head(head: Coord, s: Step) -> Coord {
+    head + s.delta()
 }
 
-fn move_tail((hx, hy): (i64, i64), (tx, ty): (i64, i64)) -> (i64, i64) {
-    let xdiff = abs(tx - hx);
-    let ydiff = abs(ty - hy);
-
-    let max_diff = cmp::max(xdiff, ydiff);
-
-    if max_diff < 2 {
-        (tx, ty)
-    } else if xdiff != 0 && ydiff != 0 {
-        (move_num_towards(tx, hx), move_num_towards(ty, hy))
-    } else if xdiff != 0 {
-        (move_num_towards(tx, hx), ty)
-    } else if ydiff != 0 {
-        (tx, move_num_towards(ty, hy))
+// The tail follows once the head is no longer touching it
+fn move_tail(head: Coord, tail: Coord) -> Coord {
+    if head.chebyshev(tail) < 2 {
+        tail
     } else {
-        panic!("Illegal state, head: {:?}, tail: {:?}", (hx, hy), (tx, ty))
+        tail.step_towards(head)
     }
 }
 
-fn move_num_towards(a: i64, b: i64) -> i64 {
-    a + signum(b - a)
-}
-
 #[test]
-fn test_move_num_towards() {
-    assert_eq!(move_num_towards(0, 2), 1);
-    assert_eq!(move_num_towards(4, 2), 3);
+fn test_parse_line() {
+    assert_eq!(parse_line("R 3").map(|s| s.len()), Ok(3));
+    assert_eq!(parse_line("X 3").map_err(|e| e.column), Err(1));
+    assert_eq!(parse_line("U x").map_err(|e| e.column), Err(3));
+    assert!(parse_line("U").is_err());
 }
 
 #[test]
 fn test_move_head() {
-    assert_eq!(move_head((0, 0), Step::R), (1, 0));
+    assert_eq!(move_head(Point::ORIGIN, Dir::Right), Point::new(1, 0));
 }
 
 #[test]
 fn test_do_step() {
-    assert_eq!(do_step(((0, 0), (0, 0)), Step::R), ((1, 0), (0, 0)));
-    assert_eq!(do_step(((1, 0), (0, 0)), Step::R), ((2, 0), (1, 0)));
+    let p = Point::new;
+    assert_eq!(do_step((p(0, 0), p(0, 0)), Dir::Right), (p(1, 0), p(0, 0)));
+    assert_eq!(do_step((p(1, 0), p(0, 0)), Dir::Right), (p(2, 0), p(1, 0)));
+    assert_eq!(do_step((p(1, -1), p(0, 0)), Dir::Up), (p(1, -2), p(1, -1)));
 }
 
 #[test]
@@ -165,7 +154,7 @@ fn test_sanity() {
 #[test]
 fn test_solve1() {
     const INPUT: &str = include_str!("../inputs/day9_test.txt");
-    let parsed = parse(INPUT);
+    let parsed = parse(INPUT).unwrap();
     let solution1 = solve1(parsed);
     assert_eq!(solution1, 13);
 }
@@ -173,7 +162,7 @@ fn test_solve1() {
 #[test]
 fn test_solve2() {
     const INPUT: &str = include_str!("../inputs/day9_test2.txt");
-    let parsed = parse(INPUT);
+    let parsed = parse(INPUT).unwrap();
     let solution = solve2(parsed);
     assert_eq!(solution, 36);
 }
@@ -0,0 +1,101 @@
+use std::fmt;
+use std::process;
+use std::str::FromStr;
+
+// A problem with the puzzle input. Line and column are 1-based; parsers
+// that only see a part of the input report positions relative to that part
+// and their callers move them with `offset`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub text: String,
+    pub message: String,
+}
+
+impl ParseError {
+    // An error about `text`, which should be a slice of `line`
+    pub fn new(line: &str, text: &str, message: &str) -> ParseError {
+        let offset = (text.as_ptr() as usize).wrapping_sub(line.as_ptr() as usize);
+        let column = if offset <= line.len() { offset + 1 } else { 1 };
+
+        ParseError {
+            line: 1,
+            column,
+            text: String::from(text),
+            message: String::from(message),
+        }
+    }
+
+    // Move the error down by `lines` lines
+    pub fn offset(mut self, lines: usize) -> ParseError {
+        self.line += lines;
+        self
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {:}, column {:}: {:}",
+            self.line, self.column, self.message
+        )?;
+        if !self.text.is_empty() {
+            write!(f, " {:?}", self.text)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// Parse `text`, a slice of `line`, as a number
+pub fn parse_num<T: FromStr>(line: &str, text: &str) -> Result<T, ParseError> {
+    text.parse()
+        .map_err(|_| ParseError::new(line, text, "expected a number, found"))
+}
+
+// Parse every line of `s` with `f`, numbering the errors by line
+pub fn parse_lines<T, F>(s: &str, f: F) -> Result<Vec<T>, ParseError>
+where
+    F: Fn(&str) -> Result<T, ParseError>,
+{
+    s.lines()
+        .enumerate()
+        .map(|(i, l)| f(l).map_err(|e| e.offset(i)))
+        .collect()
+}
+
+// Unwrap a result in a day binary, printing the error and exiting on failure
+pub fn or_exit<T, E: fmt::Display>(result: Result<T, E>) -> T {
+    result.unwrap_or_else(|e| {
+        eprintln!("{:}", e);
+        process::exit(1);
+    })
+}
+
+#[test]
+fn test_parse_error_position() {
+    let line = "move 1 from x to 3";
+    let err = parse_num::<usize>(line, &line[12..13]).unwrap_err();
+
+    assert_eq!(err.line, 1);
+    assert_eq!(err.column, 13);
+    assert_eq!(err.text, "x");
+    assert_eq!(
+        err.offset(4).to_string(),
+        "line 5, column 13: expected a number, found \"x\""
+    );
+}
+
+#[test]
+fn test_parse_lines() {
+    assert_eq!(
+        parse_lines("1\n2\n3", |l| parse_num::<i64>(l, l)),
+        Ok(vec![1, 2, 3])
+    );
+
+    let err = parse_lines("1\n2\nthree", |l| parse_num::<i64>(l, l)).unwrap_err();
+    assert_eq!((err.line, err.column), (3, 1));
+}
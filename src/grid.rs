@@ -0,0 +1,190 @@
+use crate::error::{parse_lines, ParseError};
+use crate::point::{Point, Vec2, DIRS4, DIRS8};
+use std::ops::{Index, IndexMut};
+
+// A rectangular grid stored row by row, with (0, 0) in the top left
+// corner. Points outside of it have no cell.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Grid<T> {
+    pub width: usize,
+    pub height: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T> {
+        Grid {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    // None if the rows aren't all the same width
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Option<Grid<T>> {
+        let width = rows.first().map(|row| row.len()).unwrap_or(0);
+        if rows.iter().any(|row| row.len() != width) {
+            return None;
+        }
+
+        Some(Grid {
+            width,
+            height: rows.len(),
+            cells: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    // Parse a character map, one row per line. `f` turns a character into a
+    // cell or returns the message to report it with.
+    pub fn parse<F>(s: &str, f: F) -> Result<Grid<T>, ParseError>
+    where
+        F: Fn(char) -> Result<T, &'static str>,
+    {
+        let rows = parse_lines(s, |l| {
+            l.char_indices()
+                .map(|(i, c)| f(c).map_err(|e| ParseError::new(l, &l[i..i + c.len_utf8()], e)))
+                .collect::<Result<Vec<T>, ParseError>>()
+        })?;
+
+        let width = rows.first().map(|row| row.len()).unwrap_or(0);
+        if let Some(y) = rows.iter().position(|row| row.len() != width) {
+            let line = s.lines().nth(y).unwrap_or("");
+            return Err(
+                ParseError::new(line, line, "expected a row as wide as the first, found").offset(y),
+            );
+        }
+
+        Ok(Grid::from_rows(rows).unwrap())
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        p.x >= 0 && p.y >= 0 && p.x < self.width as i64 && p.y < self.height as i64
+    }
+
+    fn index_of(&self, p: Point) -> Option<usize> {
+        if self.contains(p) {
+            Some(p.x as usize + p.y as usize * self.width)
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self, p: Point) -> Option<&T> {
+        self.index_of(p).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, p: Point) -> Option<&mut T> {
+        self.index_of(p).map(move |i| &mut self.cells[i])
+    }
+
+    // The point one step in a direction, if it's still on the grid
+    pub fn offset(&self, p: Point, dir: Vec2) -> Option<Point> {
+        Some(p + dir).filter(|n| self.contains(*n))
+    }
+
+    pub fn neighbours4(&self, p: Point) -> impl Iterator<Item = Point> + '_ {
+        DIRS4.iter().filter_map(move |d| self.offset(p, *d))
+    }
+
+    pub fn neighbours8(&self, p: Point) -> impl Iterator<Item = Point> + '_ {
+        DIRS8.iter().filter_map(move |d| self.offset(p, *d))
+    }
+
+    // The points from `p` (not included) towards the edge of the grid
+    pub fn ray(&self, p: Point, dir: Vec2) -> impl Iterator<Item = Point> + '_ {
+        std::iter::successors(self.offset(p, dir), move |p| self.offset(*p, dir))
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Point> {
+        let width = self.width.max(1);
+        (0..self.width * self.height)
+            .map(move |i| Point::new((i % width) as i64, (i / width) as i64))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn row(&self, y: usize) -> impl Iterator<Item = &T> {
+        self.cells[y * self.width..(y + 1) * self.width].iter()
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        self.cells.iter().skip(x).step_by(self.width.max(1))
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    // Back to a character map, one line per row
+    pub fn render<F: Fn(&T) -> char>(&self, f: F) -> String {
+        self.rows()
+            .map(|row| row.iter().map(&f).collect::<String>())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, p: Point) -> &T {
+        self.get(p)
+            .unwrap_or_else(|| panic!("{:} is outside the grid", p))
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, p: Point) -> &mut T {
+        self.get_mut(p)
+            .unwrap_or_else(|| panic!("{:} is outside the grid", p))
+    }
+}
+
+#[test]
+fn test_parse() {
+    let grid = Grid::parse("ab\ncd\nef", Ok).unwrap();
+
+    assert_eq!((grid.width, grid.height), (2, 3));
+    assert_eq!(grid[Point::new(1, 2)], 'f');
+    assert_eq!(grid.get(Point::new(2, 0)), None);
+    assert_eq!(grid.get(Point::new(0, -1)), None);
+    assert_eq!(grid.render(|c| *c), "ab\ncd\nef");
+
+    let digit = |c: char| c.to_digit(10).ok_or("expected a digit, found");
+    let err = Grid::parse("12\n3x", digit).unwrap_err();
+    assert_eq!((err.line, err.column, err.text.as_str()), (2, 2, "x"));
+    assert_eq!(Grid::parse("12\n3", digit).map_err(|e| e.line), Err(2));
+}
+
+#[test]
+fn test_neighbours() {
+    let grid = Grid::new(3, 3, 0);
+    let p = Point::new;
+
+    assert_eq!(
+        grid.neighbours4(p(0, 0)).collect::<Vec<_>>(),
+        [p(1, 0), p(0, 1)]
+    );
+    assert_eq!(grid.neighbours4(p(1, 1)).count(), 4);
+    assert_eq!(grid.neighbours8(p(1, 1)).count(), 8);
+    assert_eq!(grid.neighbours8(p(2, 0)).count(), 3);
+}
+
+#[test]
+fn test_lines() {
+    let grid = Grid::from_rows(vec![vec![1, 2, 3], vec![4, 5, 6]]).unwrap();
+    let p = Point::new;
+
+    assert_eq!(grid.row(1).copied().collect::<Vec<_>>(), [4, 5, 6]);
+    assert_eq!(grid.column(2).copied().collect::<Vec<_>>(), [3, 6]);
+    assert_eq!(
+        grid.ray(p(0, 0), p(1, 0)).collect::<Vec<_>>(),
+        [p(1, 0), p(2, 0)]
+    );
+    assert_eq!(grid.ray(p(2, 1), p(-1, -1)).collect::<Vec<_>>(), [p(1, 0)]);
+    assert_eq!(Grid::from_rows(vec![vec![1], vec![2, 3]]), None);
+}
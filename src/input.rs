@@ -0,0 +1,136 @@
+use crate::error::or_exit;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+// Where a puzzle input is read from. A path of "-" means stdin.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    File(PathBuf),
+    Stdin,
+}
+
+#[derive(Debug)]
+pub struct InputError {
+    pub day: u32,
+    pub source: Source,
+    pub error: io::Error,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Source::File(path) => write!(f, "{:}", path.display()),
+            Source::Stdin => write!(f, "stdin"),
+        }
+    }
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Could not read the input for day {:} from {:}: {:}",
+            self.day, self.source, self.error
+        )
+    }
+}
+
+impl std::error::Error for InputError {}
+
+impl Source {
+    pub fn from_arg(arg: Option<&str>, day: u32) -> Source {
+        match arg {
+            Some("-") => Source::Stdin,
+            Some(path) => Source::File(PathBuf::from(path)),
+            None => Source::File(default_path(day)),
+        }
+    }
+}
+
+// The checked in input for a day, e.g. inputs/day7.txt
+pub fn default_path(day: u32) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("inputs")
+        .join(format!("day{:}.txt", day))
+}
+
+pub fn read(day: u32, source: Source) -> Result<String, InputError> {
+    let result = match &source {
+        Source::File(path) => fs::read_to_string(path),
+        Source::Stdin => {
+            let mut s = String::new();
+            io::stdin().read_to_string(&mut s).map(|_| s)
+        }
+    };
+
+    result.map_err(|error| InputError { day, source, error })
+}
+
+// Read the input for a day binary from the path given as its first
+// argument, "-" for stdin or the default input file. Exits on failure.
+pub fn from_args(day: u32) -> String {
+    let arg = std::env::args().skip(1).find(|a| !a.starts_with("--"));
+    let source = Source::from_arg(arg.as_deref(), day);
+
+    or_exit(read(day, source))
+}
+
+// The value of a "--name=value" option given to a day binary, or an empty
+// string for a plain "--name" flag
+pub fn option(name: &str) -> Option<String> {
+    find_option(std::env::args().skip(1), name)
+}
+
+fn find_option<I: Iterator<Item = String>>(mut args: I, name: &str) -> Option<String> {
+    args.find_map(|a| {
+        let rest = a.strip_prefix("--")?.strip_prefix(name)?;
+        match rest.strip_prefix('=') {
+            Some(value) => Some(String::from(value)),
+            None if rest.is_empty() => Some(String::new()),
+            None => None,
+        }
+    })
+}
+
+#[test]
+fn test_source_from_arg() {
+    assert_eq!(Source::from_arg(Some("-"), 3), Source::Stdin);
+    assert_eq!(
+        Source::from_arg(Some("my_input.txt"), 3),
+        Source::File(PathBuf::from("my_input.txt"))
+    );
+    assert!(matches!(Source::from_arg(None, 3), Source::File(p) if p.ends_with("inputs/day3.txt")));
+}
+
+#[test]
+fn test_find_option() {
+    let args = |s: &str| {
+        s.split(' ')
+            .map(String::from)
+            .collect::<Vec<_>>()
+            .into_iter()
+    };
+
+    assert_eq!(
+        find_option(args("input.txt --dot"), "dot"),
+        Some(String::new())
+    );
+    assert_eq!(
+        find_option(args("--dot=pair"), "dot"),
+        Some(String::from("pair"))
+    );
+    assert_eq!(find_option(args("--dotty"), "dot"), None);
+    assert_eq!(find_option(args("input.txt"), "dot"), None);
+}
+
+#[test]
+fn test_read_missing_file() {
+    let err = read(3, Source::File(PathBuf::from("no/such/file.txt"))).unwrap_err();
+
+    assert_eq!(err.error.kind(), io::ErrorKind::NotFound);
+    assert!(err
+        .to_string()
+        .starts_with("Could not read the input for day 3 from no/such/file.txt"));
+}
@@ -1,3 +1,485 @@
+use std::env;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{Duration, Instant};
+
+use aoc22::answers::{self, Answers};
+use aoc22::bench::{self, bench, Baseline, Change};
+use aoc22::input::{self, Source};
+use aoc22::solution::solve;
+
+const USAGE: &str = "Usage:
+  aoc22 run <day|all> [--part <1|2>] [--input <path|->]
+  aoc22 bench <day|all> [--input <path|->] [--samples <n>]
+                        [--baseline <path>] [--save <path>] [--threshold <percent>]
+  aoc22 verify <day|all> [--answers <path>]
+  aoc22 record <day|all> [--part <1|2>] [--answers <path>]";
+
+const DAYS: std::ops::RangeInclusive<u32> = 1..=16;
+
+// Call the generic function `$f` with the solution for `$day`, or return
+// None if the day isn't solved
+macro_rules! with_day {
+    ($day:expr, $f:ident ( $($arg:expr),* )) => {
+        match $day {
+            1 => Some($f::<aoc22::day1::Day1>($($arg),*)),
+            2 => Some($f::<aoc22::day2::Day2>($($arg),*)),
+            3 => Some($f::<aoc22::day3::Day3>($($arg),*)),
+            4 => Some($f::<aoc22::day4::Day4>($($arg),*)),
+            5 => Some($f::<aoc22::day5::Day5>($($arg),*)),
+            6 => Some($f::<aoc22::day6::Day6>($($arg),*)),
+            7 => Some($f::<aoc22::day7::Day7>($($arg),*)),
+            8 => Some($f::<aoc22::day8::Day8>($($arg),*)),
+            9 => Some($f::<aoc22::day9::Day9>($($arg),*)),
+            10 => Some($f::<aoc22::day10::Day10>($($arg),*)),
+            11 => Some($f::<aoc22::day11::Day11>($($arg),*)),
+            12 => Some($f::<aoc22::day12::Day12>($($arg),*)),
+            13 => Some($f::<aoc22::day13::Day13>($($arg),*)),
+            14 => Some($f::<aoc22::day14::Day14>($($arg),*)),
+            15 => Some($f::<aoc22::day15::Day15>($($arg),*)),
+            16 => Some($f::<aoc22::day16::Day16>($($arg),*)),
+            _ => None,
+        }
+    };
+}
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Run,
+    Bench,
+    Verify,
+    Record,
+}
+
+#[derive(Debug, PartialEq)]
+struct Args {
+    command: Command,
+    days: Vec<u32>,
+    parts: Vec<u32>,
+    input: Option<String>,
+    bench: bench::Config,
+    baseline: Option<String>,
+    save: Option<String>,
+    threshold: f64,
+    answers: Option<String>,
+}
+
+impl Args {
+    fn new(command: Command, days: Vec<u32>) -> Args {
+        Args {
+            command,
+            days,
+            parts: vec![1, 2],
+            input: None,
+            bench: bench::Config::default(),
+            baseline: None,
+            save: None,
+            threshold: 0.1,
+            answers: None,
+        }
+    }
+}
+
 fn main() {
-    println!("Hello, world!");
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args = match parse_args(&args) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{:}\n{:}", e, USAGE);
+            process::exit(2);
+        }
+    };
+
+    let ok = match args.command {
+        Command::Run => run(&args),
+        Command::Bench => run_bench(&args),
+        Command::Verify => verify(&args),
+        Command::Record => record(&args),
+    };
+
+    if !ok {
+        process::exit(1);
+    }
+}
+
+// Read the input for a day, reporting failures
+fn read_input(args: &Args, day: u32) -> Option<String> {
+    input::read(day, Source::from_arg(args.input.as_deref(), day))
+        .map_err(|e| eprintln!("{:}", e))
+        .ok()
+}
+
+fn run(args: &Args) -> bool {
+    println!("{:>3}  {:>4}  {:>12}  Answer", "Day", "Part", "Time");
+    let mut total = Duration::ZERO;
+    let mut ok = true;
+    for &day in args.days.iter() {
+        let input = match read_input(args, day) {
+            Some(input) => input,
+            None => {
+                ok = false;
+                continue;
+            }
+        };
+
+        for part in args.parts.iter() {
+            let start = Instant::now();
+            let answer = with_day!(day, solve(&input, *part));
+            let elapsed = start.elapsed();
+            total += elapsed;
+
+            match answer {
+                Some(Ok(answer)) => print_row(day, *part, elapsed, &answer),
+                Some(Err(e)) => {
+                    // both parts parse the same input, so skip the rest
+                    eprintln!("Could not parse the input for day {:}: {:}", day, e);
+                    ok = false;
+                    break;
+                }
+                None => println!("{:>3}  {:>4}  {:>12}  not solved", day, part, "-"),
+            }
+        }
+    }
+    println!("{:>3}  {:>4}  {:>12}", "", "", format_duration(total));
+
+    ok
+}
+
+fn run_bench(args: &Args) -> bool {
+    let baseline = match args.baseline.as_deref() {
+        Some(path) => match bench::load_baseline(Path::new(path)) {
+            Ok(baseline) => Some(baseline),
+            Err(e) => {
+                eprintln!("Could not read the baseline from {:}: {:}", path, e);
+                return false;
+            }
+        },
+        None => None,
+    };
+
+    println!(
+        "{:>3}  {:>5}  {:>12}  {:>12}  {:>7}  Change",
+        "Day", "Stage", "Mean", "Std dev", "Samples"
+    );
+    let mut results = Baseline::new();
+    let mut ok = true;
+    for &day in args.days.iter() {
+        let input = match read_input(args, day) {
+            Some(input) => input,
+            None => {
+                ok = false;
+                continue;
+            }
+        };
+
+        let stats = match with_day!(day, bench(&input, &args.bench)) {
+            Some(Ok(stats)) => stats,
+            Some(Err(e)) => {
+                eprintln!("Could not parse the input for day {:}: {:}", day, e);
+                ok = false;
+                continue;
+            }
+            None => continue,
+        };
+
+        for (stage, stats) in stats {
+            let change = baseline
+                .as_ref()
+                .and_then(|b| b.get(&(day, stage)))
+                .map(|b| stats.compare(b, args.threshold));
+            if let Some(Change::Regression(_)) = change {
+                ok = false;
+            }
+
+            println!(
+                "{:>3}  {:>5}  {:>12}  {:>12}  {:>7}  {:}",
+                day,
+                stage,
+                format_duration(Duration::from_nanos(stats.mean as u64)),
+                format_duration(Duration::from_nanos(stats.std_dev() as u64)),
+                stats.samples,
+                format_change(change)
+            );
+            results.insert((day, stage), stats);
+        }
+    }
+
+    if let Some(path) = args.save.as_deref() {
+        if let Err(e) = bench::save_baseline(Path::new(path), &results) {
+            eprintln!("Could not save the baseline to {:}: {:}", path, e);
+            ok = false;
+        }
+    }
+
+    ok
+}
+
+fn answers_path(args: &Args) -> PathBuf {
+    args.answers
+        .as_deref()
+        .map(PathBuf::from)
+        .unwrap_or_else(answers::default_path)
+}
+
+fn load_answers(path: &Path) -> Option<Answers> {
+    match answers::load(path) {
+        Ok(answers) => Some(answers),
+        // nothing has been recorded yet
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Some(Answers::new()),
+        Err(e) => {
+            eprintln!(
+                "Could not read the answers from {:}: {:}",
+                path.display(),
+                e
+            );
+            None
+        }
+    }
+}
+
+// Solve every day again and compare against the recorded answers
+fn verify(args: &Args) -> bool {
+    let path = answers_path(args);
+    let expected = match load_answers(&path) {
+        Some(answers) => answers,
+        None => return false,
+    };
+
+    println!("{:>3}  {:>4}  {:>12}  Result", "Day", "Part", "Time");
+    let mut ok = true;
+    for &day in args.days.iter() {
+        let input = match read_input(args, day) {
+            Some(input) => input,
+            None => {
+                ok = false;
+                continue;
+            }
+        };
+
+        for part in args.parts.iter() {
+            let expected = match expected.get(&(day, *part)) {
+                Some(answer) => answer,
+                None => {
+                    println!("{:>3}  {:>4}  {:>12}  not recorded", day, part, "-");
+                    continue;
+                }
+            };
+
+            let start = Instant::now();
+            let answer = with_day!(day, solve(&input, *part));
+            let elapsed = start.elapsed();
+
+            match answer {
+                Some(Ok(answer)) if answer == *expected => print_row(day, *part, elapsed, "ok"),
+                Some(Ok(answer)) => {
+                    ok = false;
+                    print_row(
+                        day,
+                        *part,
+                        elapsed,
+                        &format!("MISMATCH\nexpected:\n{:}\ngot:\n{:}", expected, answer),
+                    );
+                }
+                Some(Err(e)) => {
+                    eprintln!("Could not parse the input for day {:}: {:}", day, e);
+                    ok = false;
+                    break;
+                }
+                None => println!("{:>3}  {:>4}  {:>12}  not solved", day, part, "-"),
+            }
+        }
+    }
+
+    ok
+}
+
+// Solve and store the answers once they have been confirmed on the site
+fn record(args: &Args) -> bool {
+    let path = answers_path(args);
+    let mut answers = match load_answers(&path) {
+        Some(answers) => answers,
+        None => return false,
+    };
+
+    let mut ok = true;
+    for &day in args.days.iter() {
+        let input = match read_input(args, day) {
+            Some(input) => input,
+            None => {
+                ok = false;
+                continue;
+            }
+        };
+
+        for part in args.parts.iter() {
+            match with_day!(day, solve(&input, *part)) {
+                Some(Ok(answer)) => {
+                    print_row(day, *part, Duration::ZERO, &answer);
+                    answers.insert((day, *part), answer);
+                }
+                Some(Err(e)) => {
+                    eprintln!("Could not parse the input for day {:}: {:}", day, e);
+                    ok = false;
+                    break;
+                }
+                None => {}
+            }
+        }
+    }
+
+    if let Err(e) = answers::save(&path, &answers) {
+        eprintln!("Could not save the answers to {:}: {:}", path.display(), e);
+        ok = false;
+    }
+
+    ok
+}
+
+fn parse_args(args: &[String]) -> Result<Args, String> {
+    let mut args = args.iter();
+    let command = match args.next().map(|s| s.as_str()) {
+        Some("run") => Command::Run,
+        Some("bench") => Command::Bench,
+        Some("verify") => Command::Verify,
+        Some("record") => Command::Record,
+        Some(cmd) => return Err(format!("Unknown command \"{:}\"", cmd)),
+        None => return Err(String::from("Missing command")),
+    };
+
+    let days = match args.next().map(|s| s.as_str()) {
+        Some("all") => DAYS.collect(),
+        Some(day) => vec![parse_number(day, DAYS, "day")?],
+        None => return Err(String::from("Missing day")),
+    };
+
+    let mut parsed = Args::new(command, days);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("Missing value for {:}", arg));
+        match (&parsed.command, arg.as_str()) {
+            (Command::Run | Command::Record, "--part" | "-p") => {
+                parsed.parts = vec![parse_number(value()?, 1..=2, "part")?];
+            }
+            (_, "--input" | "-i") => parsed.input = Some(value()?.clone()),
+            (Command::Bench, "--samples") => {
+                let samples = parse_number(value()?, 2..=u32::MAX, "sample count")?;
+                parsed.bench.max_samples = samples as usize;
+            }
+            (Command::Bench, "--baseline") => parsed.baseline = Some(value()?.clone()),
+            (Command::Bench, "--save") => parsed.save = Some(value()?.clone()),
+            (Command::Bench, "--threshold") => {
+                let percent = parse_number(value()?, 0..=u32::MAX, "threshold")?;
+                parsed.threshold = percent as f64 / 100.0;
+            }
+            (Command::Verify | Command::Record, "--answers") => {
+                parsed.answers = Some(value()?.clone())
+            }
+            (_, other) => return Err(format!("Unknown argument \"{:}\"", other)),
+        }
+    }
+
+    if parsed.input.is_some() && parsed.days.len() > 1 {
+        return Err(String::from("--input can only be used with a single day"));
+    }
+
+    Ok(parsed)
+}
+
+fn parse_number(s: &str, range: std::ops::RangeInclusive<u32>, what: &str) -> Result<u32, String> {
+    s.parse().ok().filter(|n| range.contains(n)).ok_or(format!(
+        "Invalid {:} \"{:}\", expected {:}-{:}",
+        what,
+        s,
+        range.start(),
+        range.end()
+    ))
+}
+
+// Multiline answers (like the day 10 CRT) continue under the answer column
+fn print_row(day: u32, part: u32, elapsed: Duration, answer: &str) {
+    let mut lines = answer.lines();
+    println!(
+        "{:>3}  {:>4}  {:>12}  {:}",
+        day,
+        part,
+        format_duration(elapsed),
+        lines.next().unwrap_or("")
+    );
+    for line in lines {
+        println!("{:>3}  {:>4}  {:>12}  {:}", "", "", "", line);
+    }
+}
+
+fn format_duration(d: Duration) -> String {
+    format!("{:.3}ms", d.as_secs_f64() * 1000.0)
+}
+
+fn format_change(change: Option<Change>) -> String {
+    match change {
+        None => String::from("-"),
+        Some(Change::Faster(c)) => format!("-{:.1}%", c * 100.0),
+        Some(Change::Slower(c)) => format!("+{:.1}%", c * 100.0),
+        Some(Change::Regression(c)) => format!("+{:.1}% REGRESSION", c * 100.0),
+    }
+}
+
+#[test]
+fn test_parse_args() {
+    let args = |s: &str| -> Vec<String> { s.split(' ').map(String::from).collect() };
+
+    assert_eq!(
+        parse_args(&args("run 7")),
+        Ok(Args::new(Command::Run, vec![7]))
+    );
+    assert_eq!(
+        parse_args(&args("run 3 --part 2")),
+        Ok(Args {
+            parts: vec![2],
+            ..Args::new(Command::Run, vec![3])
+        })
+    );
+    assert_eq!(
+        parse_args(&args("run 3 --input -")).map(|a| a.input),
+        Ok(Some(String::from("-")))
+    );
+    assert!(parse_args(&args("run all --input my_input.txt")).is_err());
+    assert_eq!(parse_args(&args("run all")).map(|a| a.days.len()), Ok(16));
+    assert!(parse_args(&args("run 0")).is_err());
+    assert!(parse_args(&args("run 3 --part 3")).is_err());
+    assert!(parse_args(&args("walk 3")).is_err());
+}
+
+#[test]
+fn test_parse_bench_args() {
+    let args = |s: &str| -> Vec<String> { s.split(' ').map(String::from).collect() };
+
+    let parsed = parse_args(&args(
+        "bench all --samples 10 --baseline base.txt --save new.txt --threshold 25",
+    ))
+    .unwrap();
+    assert_eq!(parsed.command, Command::Bench);
+    assert_eq!(parsed.bench.max_samples, 10);
+    assert_eq!(parsed.baseline, Some(String::from("base.txt")));
+    assert_eq!(parsed.save, Some(String::from("new.txt")));
+    assert_eq!(parsed.threshold, 0.25);
+
+    assert!(parse_args(&args("bench 3 --part 1")).is_err());
+    assert!(parse_args(&args("run 3 --samples 10")).is_err());
+    assert!(parse_args(&args("bench 3 --samples 1")).is_err());
+}
+
+#[test]
+fn test_parse_answers_args() {
+    let args = |s: &str| -> Vec<String> { s.split(' ').map(String::from).collect() };
+
+    let parsed = parse_args(&args("verify all --answers my_answers.txt")).unwrap();
+    assert_eq!(parsed.command, Command::Verify);
+    assert_eq!(parsed.answers, Some(String::from("my_answers.txt")));
+
+    let parsed = parse_args(&args("record 4 --part 2")).unwrap();
+    assert_eq!(parsed.command, Command::Record);
+    assert_eq!(parsed.parts, vec![2]);
+
+    assert!(parse_args(&args("verify 4 --part 2")).is_err());
+    assert!(parse_args(&args("run 4 --answers my_answers.txt")).is_err());
 }
@@ -0,0 +1,108 @@
+use std::fmt;
+
+pub const GLYPH_WIDTH: usize = 4;
+pub const GLYPH_HEIGHT: usize = 6;
+
+// Glyphs are separated by a blank column
+const SPACING: usize = GLYPH_WIDTH + 1;
+
+// The letters the puzzles draw, row by row
+const FONT: [(char, [&str; GLYPH_HEIGHT]); 18] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Y', ["#...", "#...", ".#.#", "..#.", "..#.", "..#."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+// The glyphs that aren't letters, by their position from the left
+#[derive(Clone, Debug, PartialEq)]
+pub struct OcrError {
+    pub unrecognized: Vec<(usize, Vec<String>)>,
+}
+
+impl fmt::Display for OcrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Could not recognize the glyphs at")?;
+        for (i, _) in self.unrecognized.iter() {
+            write!(f, " {:}", i + 1)?;
+        }
+        for (i, glyph) in self.unrecognized.iter() {
+            write!(f, "\nglyph {:}:\n{:}", i + 1, glyph.join("\n"))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for OcrError {}
+
+pub fn letter(glyph: &[String]) -> Option<char> {
+    FONT.iter()
+        .find(|(_, rows)| rows.iter().eq(glyph.iter()))
+        .map(|(c, _)| *c)
+}
+
+// Read the letters from a screen of '#' and '.' pixels `width` wide. Only
+// the top rows the height of a glyph are read.
+pub fn recognize(pixels: &[char], width: usize) -> Result<String, OcrError> {
+    let rows: Vec<&[char]> = pixels.chunks(width).take(GLYPH_HEIGHT).collect();
+    let glyphs: Vec<Vec<String>> = (0..(width + 1) / SPACING)
+        .map(|i| {
+            rows.iter()
+                .map(|row| row.iter().skip(i * SPACING).take(GLYPH_WIDTH).collect())
+                .collect()
+        })
+        .collect();
+
+    let letters: Vec<Option<char>> = glyphs.iter().map(|g| letter(g)).collect();
+    let unrecognized: Vec<(usize, Vec<String>)> = letters
+        .iter()
+        .zip(glyphs)
+        .enumerate()
+        .filter(|(_, (l, _))| l.is_none())
+        .map(|(i, (_, g))| (i, g))
+        .collect();
+
+    if unrecognized.is_empty() {
+        Ok(letters.into_iter().flatten().collect())
+    } else {
+        Err(OcrError { unrecognized })
+    }
+}
+
+#[test]
+fn test_recognize() {
+    let screen = [
+        "#..#.####.",
+        "#..#.#....",
+        "####.###..",
+        "#..#.#....",
+        "#..#.#....",
+        "#..#.####.",
+    ];
+    let pixels: Vec<char> = screen.iter().flat_map(|row| row.chars()).collect();
+
+    assert_eq!(recognize(&pixels, 10), Ok(String::from("HE")));
+
+    let mut broken = pixels.clone();
+    broken[16] = '#';
+    let err = recognize(&broken, 10).unwrap_err();
+    assert_eq!(err.unrecognized.len(), 1);
+    assert_eq!(err.unrecognized[0].0, 1);
+    assert!(err
+        .to_string()
+        .starts_with("Could not recognize the glyphs at 2\nglyph 2:\n####\n##..\n"));
+}
@@ -0,0 +1,223 @@
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+// A position or a vector on a 2D plane. y grows downwards like in the
+// puzzle inputs, so Up is (0, -1).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+pub type Vec2 = Point;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Dir {
+    Up,
+    Left,
+    Right,
+    Down,
+}
+
+// The four neighbours, in reading order
+pub const DIRS4: [Vec2; 4] = [
+    Point::new(0, -1),
+    Point::new(-1, 0),
+    Point::new(1, 0),
+    Point::new(0, 1),
+];
+
+// All eight neighbours, in reading order
+pub const DIRS8: [Vec2; 8] = [
+    Point::new(-1, -1),
+    Point::new(0, -1),
+    Point::new(1, -1),
+    Point::new(-1, 0),
+    Point::new(1, 0),
+    Point::new(-1, 1),
+    Point::new(0, 1),
+    Point::new(1, 1),
+];
+
+impl Point {
+    pub const ORIGIN: Point = Point::new(0, 0);
+
+    pub const fn new(x: i64, y: i64) -> Point {
+        Point { x, y }
+    }
+
+    pub fn manhattan(self, other: Point) -> i64 {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
+    }
+
+    pub fn chebyshev(self, other: Point) -> i64 {
+        (self.x - other.x).abs().max((self.y - other.y).abs())
+    }
+
+    // A step of at most one in each axis in the same direction
+    pub fn signum(self) -> Vec2 {
+        Point::new(self.x.signum(), self.y.signum())
+    }
+
+    // The point one step closer to `other`, diagonally if needed
+    pub fn step_towards(self, other: Point) -> Point {
+        self + (other - self).signum()
+    }
+
+    pub fn neighbours4(self) -> impl Iterator<Item = Point> {
+        DIRS4.into_iter().map(move |d| self + d)
+    }
+
+    pub fn neighbours8(self) -> impl Iterator<Item = Point> {
+        DIRS8.into_iter().map(move |d| self + d)
+    }
+}
+
+impl Dir {
+    pub const ALL: [Dir; 4] = [Dir::Up, Dir::Left, Dir::Right, Dir::Down];
+
+    pub fn delta(self) -> Vec2 {
+        match self {
+            Dir::Up => Point::new(0, -1),
+            Dir::Left => Point::new(-1, 0),
+            Dir::Right => Point::new(1, 0),
+            Dir::Down => Point::new(0, 1),
+        }
+    }
+
+    // The direction of a single orthogonal step
+    pub fn from_delta(d: Vec2) -> Option<Dir> {
+        Dir::ALL.into_iter().find(|dir| dir.delta() == d)
+    }
+
+    // "U", "L", "R" or "D"
+    pub fn from_letter(c: char) -> Option<Dir> {
+        match c {
+            'U' => Some(Dir::Up),
+            'L' => Some(Dir::Left),
+            'R' => Some(Dir::Right),
+            'D' => Some(Dir::Down),
+            _ => None,
+        }
+    }
+
+    pub fn arrow(self) -> char {
+        match self {
+            Dir::Up => '^',
+            Dir::Left => '<',
+            Dir::Right => '>',
+            Dir::Down => 'v',
+        }
+    }
+
+    pub fn opposite(self) -> Dir {
+        match self {
+            Dir::Up => Dir::Down,
+            Dir::Left => Dir::Right,
+            Dir::Right => Dir::Left,
+            Dir::Down => Dir::Up,
+        }
+    }
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({:}, {:})", self.x, self.y)
+    }
+}
+
+impl From<(i64, i64)> for Point {
+    fn from((x, y): (i64, i64)) -> Point {
+        Point::new(x, y)
+    }
+}
+
+impl From<Point> for (i64, i64) {
+    fn from(p: Point) -> (i64, i64) {
+        (p.x, p.y)
+    }
+}
+
+impl From<Dir> for Vec2 {
+    fn from(d: Dir) -> Vec2 {
+        d.delta()
+    }
+}
+
+impl Add for Point {
+    type Output = Point;
+
+    fn add(self, other: Point) -> Point {
+        Point::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+
+    fn sub(self, other: Point) -> Point {
+        Point::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl AddAssign for Point {
+    fn add_assign(&mut self, other: Point) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Point {
+    fn sub_assign(&mut self, other: Point) {
+        *self = *self - other;
+    }
+}
+
+impl Mul<i64> for Point {
+    type Output = Point;
+
+    fn mul(self, n: i64) -> Point {
+        Point::new(self.x * n, self.y * n)
+    }
+}
+
+impl Neg for Point {
+    type Output = Point;
+
+    fn neg(self) -> Point {
+        Point::new(-self.x, -self.y)
+    }
+}
+
+#[test]
+fn test_arithmetic() {
+    let a = Point::new(1, 2);
+    let b = Point::new(4, -2);
+
+    assert_eq!(a + b, Point::new(5, 0));
+    assert_eq!(a - b, Point::new(-3, 4));
+    assert_eq!(-a * 3, Point::new(-3, -6));
+    assert_eq!(Point::from((1, 2)), a);
+    assert_eq!(a.step_towards(b), Point::new(2, 1));
+    assert_eq!(a.step_towards(Point::new(1, 0)), Point::new(1, 1));
+}
+
+#[test]
+fn test_distances() {
+    let a = Point::new(1, 2);
+    let b = Point::new(4, -2);
+
+    assert_eq!(a.manhattan(b), 7);
+    assert_eq!(a.chebyshev(b), 4);
+    assert_eq!(a.neighbours8().filter(|n| a.chebyshev(*n) == 1).count(), 8);
+}
+
+#[test]
+fn test_dir() {
+    for dir in Dir::ALL {
+        assert_eq!(Dir::from_delta(dir.delta()), Some(dir));
+        assert_eq!(dir.delta() + dir.opposite().delta(), Point::ORIGIN);
+    }
+    assert_eq!(Dir::from_letter('R'), Some(Dir::Right));
+    assert_eq!(Dir::from_letter('X'), None);
+    assert_eq!(Dir::from_delta(Point::new(1, 1)), None);
+}
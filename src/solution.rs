@@ -0,0 +1,24 @@
+use crate::error::ParseError;
+use std::fmt::Display;
+
+// A day's puzzle: parse the input once and solve both parts from it.
+pub trait Solution {
+    type Input;
+    type Answer1: Display;
+    type Answer2: Display;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError>;
+    fn part1(input: &Self::Input) -> Self::Answer1;
+    fn part2(input: &Self::Input) -> Self::Answer2;
+}
+
+// Parse the input and solve one part of it
+pub fn solve<S: Solution>(input: &str, part: u32) -> Result<String, ParseError> {
+    let parsed = S::parse(input)?;
+    let answer = match part {
+        1 => S::part1(&parsed).to_string(),
+        _ => S::part2(&parsed).to_string(),
+    };
+
+    Ok(answer)
+}